@@ -0,0 +1,35 @@
+// Composites the faces of the weighted blended passes over a view, with a quad covering it
+
+#import bevy_pbr::mesh_view_bindings::view
+#ifdef TONEMAP_IN_SHADER
+#import bevy_core_pipeline::tonemapping::tone_mapping
+#endif
+
+@group(2) @binding(0) var accumulation_texture: texture_2d<f32>;
+@group(2) @binding(1) var revealage_texture: texture_2d<f32>;
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> @builtin(position) vec4<f32> {
+    // The quad is 2 by 2, the size of clip space, wherever the camera is
+    return vec4(vertex.position.xy, 0.0, 1.0);
+}
+
+@fragment
+fn fragment(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    // The targets are the size of the view rather than of the whole window
+    let pixel = vec2<i32>(position.xy - view.viewport.xy);
+    let revealage = textureLoad(revealage_texture, pixel, 0).r;
+    if revealage >= 1.0 {
+        discard;
+    }
+    let accumulation = textureLoad(accumulation_texture, pixel, 0);
+    var color = vec4(accumulation.rgb / max(accumulation.a, 1e-5), 1.0 - revealage);
+#ifdef TONEMAP_IN_SHADER
+    color = tone_mapping(color, view.color_grading);
+#endif
+    return color;
+}
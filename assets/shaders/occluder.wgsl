@@ -0,0 +1,7 @@
+// Edges and markers in the weighted blended passes, which only write their depth so that they
+// hide the faces behind them
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return vec4(0.0);
+}
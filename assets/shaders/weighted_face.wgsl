@@ -0,0 +1,39 @@
+// Faces of the hypercubes in the weighted blended passes, which add up their weighted colors
+// in the accumulation target and multiply together how much of the background they let
+// through in the revealage target
+
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    mesh_view_bindings::view,
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::apply_pbr_lighting,
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+}
+
+// Weight of a fragment at distance `z` from the camera, in units of the size of a hypercube,
+// as in equation 9 of McGuire and Bavoil (2013)
+fn depth_weight(z: f32) -> f32 {
+    return clamp(10.0 / (1e-5 + pow(z / 5.0, 2.0) + pow(z / 200.0, 6.0)), 1e-2, 3e3);
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    let pbr_input = pbr_input_from_standard_material(in, is_front);
+    var color = pbr_input.material.base_color;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+        color = apply_pbr_lighting(pbr_input);
+    }
+    let alpha = pbr_input.material.base_color.a;
+
+    var out: FragmentOutput;
+#ifdef ACCUMULATION
+    let weight = alpha * depth_weight(distance(in.world_position.xyz, view.world_position));
+    out.color = vec4(color.rgb * alpha, alpha) * weight;
+#else
+    out.color = vec4(alpha);
+#endif
+    return out;
+}
@@ -0,0 +1,41 @@
+//! Geometry of n-dimensional hypercubes, without any rendering dependencies.
+//!
+//! [`NCube`] generates the vertices, edges, faces and cells of a hypercube, rotates it in any
+//! of its planes and projects it down to 3 dimensions, in perspective or orthographically.
+//! [`NSection`] cuts it through its center and [`NNet`] unfolds it into its net, while
+//! [`NSkeleton`] and [`walk`] explore the graph of its vertices and edges. [`Mat`] holds the matrices behind the rotations
+//! and projections.
+//!
+//! ```
+//! use ncube_core::{ExtendedMathOps, NCube};
+//!
+//! let mut tesseract = NCube::new(4, 2.0);
+//! assert_eq!(tesseract.vertices.0.len(), 16);
+//! assert_eq!(tesseract.edges.0.len(), 32);
+//!
+//! let planes = usize::pair_permutations(0, 3);
+//! let angles = planes
+//!     .iter()
+//!     .map(|plane| if *plane == (0, 3) { 0.5 } else { 0.0 })
+//!     .collect::<Vec<_>>();
+//! tesseract.rotate(&planes, &angles);
+//! let projected: Vec<[f64; 3]> = tesseract.perspective_project_vertices();
+//! assert_eq!(projected.len(), 16);
+//! ```
+
+#![warn(missing_docs)]
+
+mod mat;
+mod ncube;
+mod net;
+mod section;
+mod skeleton;
+mod vec;
+pub mod walk;
+
+pub use mat::Mat;
+pub use ncube::{ExtendedMathOps, NCell, NCube, NEdges, NFaces, NVertices};
+pub use net::NNet;
+pub use section::NSection;
+pub use skeleton::NSkeleton;
+pub use vec::MathOps;
@@ -1,19 +1,22 @@
-#[macro_export]
+/// Element `i`, `j` of a matrix
 macro_rules! emat {
     ($m:ident [ $i:expr ] [ $j:expr ]) => {
         $m.matrix[($i * $m.cols) + $j]
     };
 }
 
+/// Dense matrix of `f64`, stored row by row
 #[derive(Debug, Clone)]
 pub struct Mat {
+    /// Number of rows
     pub rows: usize,
+    /// Number of columns
     pub cols: usize,
     matrix: Vec<f64>,
 }
 
-#[allow(dead_code)]
 impl Mat {
+    /// Creates a matrix from its rows, which must all have the same length
     pub fn new(mat: &[&[f64]]) -> Self {
         let row_len = mat[0].len();
         assert!(mat.iter().all(|r| row_len == r.len()));
@@ -24,6 +27,7 @@ impl Mat {
         }
     }
 
+    /// Creates a matrix with ones on the main diagonal and zeros elsewhere
     pub fn identity(rows: usize, cols: usize) -> Self {
         let mut m = Self::fill(0.0, rows, cols);
         for i in 0..rows {
@@ -40,6 +44,7 @@ impl Mat {
         }
     }
 
+    /// Creates a matrix with every element set to `n`
     pub fn fill(n: f64, rows: usize, cols: usize) -> Self {
         Self {
             rows,
@@ -48,6 +53,8 @@ impl Mat {
         }
     }
 
+    /// Creates the matrix of a rotation by `theta` radians in the plane of the dimensions
+    /// `plane`
     pub fn rotation(rows: usize, cols: usize, plane: (usize, usize), theta: f64) -> Self {
         let mut m = Self::identity(rows, cols);
         let assign_element = |element: &mut f64, v: f64| {
@@ -60,11 +67,12 @@ impl Mat {
         m
     }
 
+    /// Composes the rotations by each of `thetas` in the matching `planes`, in order
     pub fn from_rotations(
         rows: usize,
         cols: usize,
-        planes: &Vec<(usize, usize)>,
-        thetas: &Vec<f64>,
+        planes: &[(usize, usize)],
+        thetas: &[f64],
     ) -> Self {
         let mut m = Self::identity(rows, cols);
         for i in 0..planes.len() {
@@ -76,6 +84,7 @@ impl Mat {
         m
     }
 
+    /// Checks whether the matrix has as many rows as columns
     pub fn is_square(&self) -> bool {
         self.rows == self.cols
     }
@@ -122,8 +131,8 @@ impl std::ops::Mul<Vec<f64>> for Mat {
         assert_eq!(rhs.len(), self.cols);
         let v = rhs.clone();
         rhs.truncate(self.rows);
-        for i in 0..self.rows {
-            rhs[i] = v
+        for (i, x) in rhs.iter_mut().enumerate() {
+            *x = v
                 .iter()
                 .enumerate()
                 .map(|(j, v)| v * emat!(self[i][j]))
@@ -207,6 +216,14 @@ mod tests {
         );
     }
     #[test]
+    fn mat_rotation() {
+        let m = Mat::rotation(3, 3, (0, 1), std::f64::consts::FRAC_PI_2);
+        let v = m * vec![1.0, 0.0, 2.0];
+        assert!((v[0] - 0.0).abs() < 1e-12);
+        assert!((v[1] - 1.0).abs() < 1e-12);
+        assert_eq!(v[2], 2.0);
+    }
+    #[test]
     fn mat_vec_mul() {
         let a = Mat::new(&[
             &[1.0, 2.0, 3.0, 2.0],
@@ -0,0 +1,482 @@
+use crate::mat::Mat;
+use crate::section::NSection;
+use crate::vec::MathOps;
+
+/// Combinatorics on integers
+pub trait ExtendedMathOps
+where
+    Self: Sized,
+{
+    /// Computes the factorial
+    fn factorial(&self) -> Self;
+    /// Computes the number of ways to choose `k` elements out of `self`
+    fn permute(&self, k: Self) -> Self;
+    /// Generates the pairs `(i, j)` with `from <= i < j <= to`, which are the planes of
+    /// rotation when `from` and `to` are dimension indices
+    fn pair_permutations(from: Self, to: Self) -> Vec<(Self, Self)>;
+}
+impl ExtendedMathOps for usize {
+    /// Unoptimized basic factorial implementation
+    fn factorial(&self) -> usize {
+        let mut f: usize = 1;
+        for i in 1..=*self {
+            f *= i;
+        }
+        f
+    }
+    fn permute(&self, k: Self) -> Self {
+        self.factorial() / (k.factorial() * (self - k).factorial())
+    }
+    /// Generates a vector with the permutations of 2 integers within a range
+    fn pair_permutations(from: Self, to: Self) -> Vec<(Self, Self)> {
+        (from..=to)
+            .flat_map(|i| ((i + 1)..=to).map(|j| (i, j)).collect::<Vec<_>>())
+            .collect()
+    }
+}
+
+/// Hypercube centered at the origin, with its edges parallel to the axes
+#[derive(Debug, Clone)]
+pub struct NCube {
+    /// Number of dimensions
+    pub dimensions: usize,
+    /// Edge length
+    pub size: f64,
+    /// Cartesian coordinates of the vertices of the hypercube.
+    pub vertices: NVertices,
+    /// Vertex indices of the edges of the hypercube.
+    pub edges: NEdges,
+    /// Vertex indices of the 2D faces of the hypercube.
+    pub faces: NFaces,
+}
+
+/// Vertices of a hypercube. The bits of each index are the signs of its coordinates, a set bit
+/// meaning negative.
+#[derive(Debug, Clone)]
+pub struct NVertices(pub Vec<Vec<f64>>);
+
+#[derive(Debug, Clone)]
+/// Each edge is composed of 2 vertices (index)
+pub struct NEdges(pub Vec<(usize, usize)>);
+
+#[derive(Debug, Clone)]
+/// Each face is a polygon composed of its vertices (index), in cyclic order
+pub struct NFaces(pub Vec<Vec<usize>>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// An m dimensional cell of the hypercube, made of the vertices that have each of the `fixed`
+/// dimensions set to the given side (`true` is positive)
+pub struct NCell {
+    /// Dimensions held fixed, with the side of each
+    pub fixed: Vec<(usize, bool)>,
+}
+
+impl NCell {
+    /// Checks whether the vertex of index `i` lies within the cell
+    pub fn contains(&self, i: usize) -> bool {
+        self.fixed
+            .iter()
+            .all(|(d, positive)| ((i >> d) & 1 == 0) == *positive)
+    }
+
+    /// Smallest cell of an `n` dimensional hypercube that contains all of the `vertices`, e.g.
+    /// the vertex itself, or the edge or face that they make up
+    pub fn spanned(vertices: &[usize], n: usize) -> Self {
+        let first = vertices.first().copied().unwrap_or_default();
+        let mask = vertices.iter().fold(0, |mask, i| mask | (first ^ i));
+        Self {
+            fixed: (0..n)
+                .filter(|d| (mask >> d) & 1 == 0)
+                .map(|d| (d, (first >> d) & 1 == 0))
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for NCell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fixed = self
+            .fixed
+            .iter()
+            .map(|(d, positive)| format!("q{}{}", d + 1, if *positive { "+" } else { "-" }))
+            .collect::<Vec<_>>();
+        write!(f, "{}", fixed.join(" "))
+    }
+}
+
+impl std::fmt::Display for NVertices {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "[")?;
+        for edge in &self.0 {
+            write!(f, "  [ ")?;
+            for i in edge {
+                write!(
+                    f,
+                    "{} ",
+                    if *i > 0.0 {
+                        format!("+{i}")
+                    } else if *i == 0.0 {
+                        format!(" {i}")
+                    } else {
+                        format!("{i}")
+                    }
+                )?;
+            }
+            writeln!(f, "],")?;
+        }
+        writeln!(f, "]")
+    }
+}
+
+impl NCube {
+    /// Creates an `n` dimensional hypercube of size `s`.
+    pub fn new(n: usize, s: f64) -> Self {
+        let vertices = Self::_vertices(n, s);
+        Self {
+            dimensions: n,
+            size: s,
+            faces: Self::_faces(n),
+            edges: Self::_edges(&vertices, n),
+            vertices,
+        }
+    }
+
+    /// Computes how many m dimensional faces the hypercube has
+    pub fn face_count(&self, m: usize) -> usize {
+        Self::_face_count(self.dimensions, m)
+    }
+
+    /// Counts the faces of each dimension, from the vertices up to the hypercube itself
+    pub fn f_vector(&self) -> Vec<usize> {
+        (0..=self.dimensions).map(|m| self.face_count(m)).collect()
+    }
+
+    /// Computes the Euler characteristic of the boundary of the hypercube, the alternating sum
+    /// of its face counts below the hypercube itself. It is `1 + (-1)^(n - 1)`, that of a sphere.
+    pub fn euler_characteristic(&self) -> i64 {
+        self.f_vector()
+            .iter()
+            .take(self.dimensions)
+            .enumerate()
+            .map(|(m, f)| if m % 2 == 0 { *f as i64 } else { -(*f as i64) })
+            .sum()
+    }
+
+    /// Computes the order of the symmetry group of the hypercube, `2^n · n!`, which is also the
+    /// automorphism group of its skeleton: any permutation of the axes, then any reflections
+    pub fn symmetry_count(&self) -> usize {
+        2_usize.pow(self.dimensions as u32) * self.dimensions.factorial()
+    }
+
+    /// Computes the diagonal of the hypercube
+    pub fn diagonal_length(&self) -> f64 {
+        self.size * (self.dimensions as f64).sqrt()
+    }
+
+    /// Computes the plane of the 2 dimensions a face is parallel to.
+    /// Works off the vertex indices, since these encode each vertex's coordinate signs as bits.
+    pub fn face_plane(&self, face: &[usize]) -> (usize, usize) {
+        let mask = face.iter().fold(0, |mask, i| mask | (face[0] ^ i));
+        let i = mask.trailing_zeros() as usize;
+        let j = (mask & !(1 << i)).trailing_zeros() as usize;
+        (i, j)
+    }
+
+    /// Lists the m dimensional cells of the hypercube, e.g. its facets when `m` is `n - 1`
+    pub fn cells(&self, m: usize) -> Vec<NCell> {
+        let n = self.dimensions;
+        let mut cells = Vec::with_capacity(self.face_count(m));
+        for mask in 0..2_usize.pow(n as u32) {
+            if mask.count_ones() as usize != n - m {
+                continue;
+            }
+            let dimensions = (0..n).filter(|d| (mask >> d) & 1 == 1).collect::<Vec<_>>();
+            for sides in 0..2_usize.pow((n - m) as u32) {
+                let fixed = dimensions
+                    .iter()
+                    .enumerate()
+                    .map(|(k, d)| (*d, (sides >> k) & 1 == 0))
+                    .collect();
+                cells.push(NCell { fixed });
+            }
+        }
+        cells
+    }
+
+    fn _face_count(n: usize, m: usize) -> usize {
+        2_usize.pow((n - m).try_into().unwrap()) * n.permute(m)
+    }
+
+    fn _vertices(n: usize, s: f64) -> NVertices {
+        let s = s / 2.0;
+        let v_count = Self::_face_count(n, 0);
+        let vertices = (0..v_count)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        let direction =
+                            -1 + 2 * ((i / 2_usize.pow(j.try_into().unwrap())) % 2 == 0) as i8;
+                        s * direction as f64
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<Vec<_>>>();
+        NVertices(vertices)
+    }
+
+    fn _edges(vertices: &NVertices, n: usize) -> NEdges {
+        let e_count = Self::_face_count(n, 1);
+        let mut edges = Vec::with_capacity(e_count);
+        for i in 0..vertices.0.len() {
+            let vertex_a = &vertices.0[i];
+            vertices
+                .0
+                .iter()
+                .enumerate()
+                .skip(i)
+                .for_each(|(j, vertex_b)| {
+                    if Vec::shared_dimensions(&[vertex_a, vertex_b]).len() == n - 1 {
+                        edges.push((i, j));
+                    }
+                });
+            if edges.len() == e_count {
+                break;
+            }
+        }
+        NEdges(edges)
+    }
+
+    /// Each face is the cycle of vertices obtained by flipping the signs of 2 dimensions in turn,
+    /// starting from a vertex that has both of them positive.
+    fn _faces(n: usize) -> NFaces {
+        let v_count = Self::_face_count(n, 0);
+        let faces = usize::pair_permutations(0, n - 1)
+            .iter()
+            .flat_map(|(i, j)| {
+                let (a, b) = (1 << i, 1 << j);
+                (0..v_count)
+                    .filter(move |v| v & (a | b) == 0)
+                    .map(move |v| vec![v, v | a, v | a | b, v | b])
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(faces.len(), Self::_face_count(n, 2));
+        NFaces(faces)
+    }
+
+    /// Rotates the vertices by each of `theta_rads` in the matching `planes`, in order
+    pub fn rotate(&mut self, planes: &[(usize, usize)], theta_rads: &[f64]) -> &mut Self {
+        for vertex in &mut self.vertices.0 {
+            *vertex = Mat::from_rotations(self.dimensions, self.dimensions, planes, theta_rads)
+                * vertex.clone();
+        }
+        self
+    }
+
+    /// Turns `points`, given where the hypercube was created, along with the hypercube's
+    /// rotations, working them out from the edges at its first vertex
+    pub fn orient(&self, points: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let n = self.dimensions;
+        let origin = &self.vertices.0[0];
+        let axes = (0..n)
+            .map(|d| {
+                let vertex = &self.vertices.0[1 << d];
+                (0..n)
+                    .map(|k| (origin[k] - vertex[k]) / self.size)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        points
+            .iter()
+            .map(|point| {
+                (0..n)
+                    .map(|k| (0..n).map(|d| point[d] * axes[d][k]).sum())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Projects the vertices down to 3 dimensions, one dimension at a time, each with a
+    /// perspective projection from a point at 1.5 times the size along the dropped axis
+    pub fn perspective_project_vertices(&self) -> Vec<[f64; 3]> {
+        self.perspective_project(&self.vertices.0)
+    }
+
+    /// Projects any `points` of the hypercube's space down to 3 dimensions, the same way as
+    /// its vertices
+    pub fn perspective_project(&self, points: &[Vec<f64>]) -> Vec<[f64; 3]> {
+        Self::perspective_project_points(points, self.dimensions, self.size)
+    }
+
+    /// Projects `points` of an `n` dimensional space down to 3 dimensions like the vertices of
+    /// a hypercube of size `s`
+    pub fn perspective_project_points(points: &[Vec<f64>], n: usize, s: f64) -> Vec<[f64; 3]> {
+        let projection_count = n - 3;
+        let proj_m = |from_d: usize, to_d: usize, q: f64| {
+            let f = s / (s * 1.5 - q);
+            Mat::identity(to_d, from_d) * f
+        };
+        let mut v = points.to_vec();
+        for i in 0..projection_count {
+            let curr_d = n - i;
+            let target_d = curr_d - 1;
+            for vertex in &mut v {
+                let m = proj_m(curr_d, target_d, vertex[curr_d - 1]);
+                *vertex = m * vertex.clone();
+            }
+        }
+        v.iter().map(|x| [x[0], x[1], x[2]]).collect()
+    }
+
+    /// Projects the vertices down to 3 dimensions by dropping the coordinates past the third,
+    /// so that parallel edges stay parallel and every cell keeps its size
+    pub fn orthographic_project_vertices(&self) -> Vec<[f64; 3]> {
+        self.orthographic_project(&self.vertices.0)
+    }
+
+    /// Projects any `points` of the hypercube's space down to 3 dimensions, the same way as
+    /// its vertices with [`NCube::orthographic_project_vertices`]
+    pub fn orthographic_project(&self, points: &[Vec<f64>]) -> Vec<[f64; 3]> {
+        points.iter().map(|x| [x[0], x[1], x[2]]).collect()
+    }
+
+    /// Cuts the hypercube through its center, across its last axis
+    pub fn cross_section(&self) -> NSection {
+        NSection::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn get_vertices() {
+        let target_vertices = vec![
+            vec![1.0, 1.0, 1.0, 1.0],
+            vec![-1.0, 1.0, 1.0, 1.0],
+            vec![1.0, -1.0, 1.0, 1.0],
+            vec![-1.0, -1.0, 1.0, 1.0],
+            vec![1.0, 1.0, -1.0, 1.0],
+            vec![-1.0, 1.0, -1.0, 1.0],
+            vec![1.0, -1.0, -1.0, 1.0],
+            vec![-1.0, -1.0, -1.0, 1.0],
+            vec![1.0, 1.0, 1.0, -1.0],
+            vec![-1.0, 1.0, 1.0, -1.0],
+            vec![1.0, -1.0, 1.0, -1.0],
+            vec![-1.0, -1.0, 1.0, -1.0],
+            vec![1.0, 1.0, -1.0, -1.0],
+            vec![-1.0, 1.0, -1.0, -1.0],
+            vec![1.0, -1.0, -1.0, -1.0],
+            vec![-1.0, -1.0, -1.0, -1.0],
+        ];
+        let tesseract_vertices = NCube::new(4, 2.0).vertices;
+        println!("Tesseract vertices: {tesseract_vertices}");
+        assert_eq!(tesseract_vertices.0, target_vertices);
+    }
+    #[test]
+    fn get_face_count() {
+        let target_face_count = vec![16, 32, 24, 8, 1];
+        let tesseract_face_count = (0..=4)
+            .map(|m| NCube::new(4, 1.0).face_count(m))
+            .collect::<Vec<_>>();
+        println!("Tesseract face count: {tesseract_face_count:?}");
+        assert_eq!(target_face_count, tesseract_face_count);
+        assert_eq!(NCube::new(4, 1.0).f_vector(), target_face_count);
+    }
+    #[test]
+    fn get_euler_characteristic() {
+        for n in 2..=9 {
+            let ncube = NCube::new(n, 1.0);
+            assert_eq!(ncube.euler_characteristic(), 1 + (-1_i64).pow(n as u32 - 1));
+        }
+        assert_eq!(NCube::new(3, 1.0).symmetry_count(), 48);
+        assert_eq!(NCube::new(4, 1.0).symmetry_count(), 384);
+    }
+    #[test]
+    fn get_cells() {
+        let tesseract = NCube::new(4, 2.0);
+        let cubes = tesseract.cells(3);
+        assert_eq!(cubes.len(), tesseract.face_count(3));
+        for cube in &cubes {
+            let vertices = (0..tesseract.vertices.0.len())
+                .filter(|i| cube.contains(*i))
+                .collect::<Vec<_>>();
+            assert_eq!(vertices.len(), 8);
+            let (d, positive) = cube.fixed[0];
+            for i in vertices {
+                assert_eq!(tesseract.vertices.0[i][d] > 0.0, positive);
+            }
+            let edges = tesseract
+                .edges
+                .0
+                .iter()
+                .filter(|(i, j)| cube.contains(*i) && cube.contains(*j))
+                .count();
+            assert_eq!(edges, 12);
+            let vertices = (0..16).filter(|i| cube.contains(*i)).collect::<Vec<_>>();
+            assert_eq!(&NCell::spanned(&vertices, 4), cube);
+        }
+        assert_eq!(tesseract.cells(2).len(), tesseract.face_count(2));
+        let vertex = NCell::spanned(&[0b0101], 4);
+        assert_eq!(vertex.fixed.len(), 4);
+        assert!(vertex.contains(0b0101) && !vertex.contains(0b0100));
+    }
+    #[test]
+    fn get_faces() {
+        let tesseract = NCube::new(4, 2.0);
+        assert_eq!(tesseract.faces.0.len(), tesseract.face_count(2));
+        for face in &tesseract.faces.0 {
+            assert_eq!(face.len(), 4);
+            // Consecutive vertices share an edge
+            for k in 0..face.len() {
+                let (i, j) = (face[k], face[(k + 1) % face.len()]);
+                assert!(tesseract.edges.0.contains(&(i.min(j), i.max(j))));
+            }
+        }
+    }
+    #[test]
+    fn rotate_and_project() {
+        let mut cube = NCube::new(3, 2.0);
+        let vertices = cube.vertices.0.clone();
+        let projected = cube.perspective_project_vertices();
+        assert!(projected.iter().zip(&vertices).all(|(p, v)| p[..] == v[..]));
+        cube.rotate(&[(0, 1), (1, 2)], &[0.3, 1.2]);
+        for (rotated, vertex) in cube.vertices.0.iter().zip(&vertices) {
+            assert!((rotated.magnitude() - vertex.magnitude()).abs() < 1e-12);
+        }
+        for (oriented, rotated) in cube.orient(&vertices).iter().zip(&cube.vertices.0) {
+            assert!(oriented
+                .iter()
+                .zip(rotated)
+                .all(|(a, b)| (a - b).abs() < 1e-12));
+        }
+        let tesseract = NCube::new(4, 2.0);
+        let projected = tesseract.perspective_project_vertices();
+        // The cell nearer to the projection point appears larger
+        assert_eq!(projected[0], [1.0, 1.0, 1.0]);
+        assert_eq!(projected[8], [0.5, 0.5, 0.5]);
+        // Both cells overlap when the fourth axis is dropped
+        let projected = tesseract.orthographic_project_vertices();
+        assert_eq!(projected[0], projected[8]);
+    }
+    #[test]
+    fn get_face_planes() {
+        let penteract = NCube::new(5, 1.0);
+        for plane in usize::pair_permutations(0, 4) {
+            let faces = penteract
+                .faces
+                .0
+                .iter()
+                .filter(|face| penteract.face_plane(face) == plane)
+                .collect::<Vec<_>>();
+            assert_eq!(faces.len(), 2_usize.pow(5 - 2));
+            for face in faces {
+                let vertices = face
+                    .iter()
+                    .map(|i| &penteract.vertices.0[*i])
+                    .collect::<Vec<_>>();
+                let shared = Vec::shared_dimensions(&vertices);
+                assert!(!shared.contains(&plane.0) && !shared.contains(&plane.1));
+            }
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use crate::ncube::{NCell, NCube, NEdges, NFaces};
+use std::f64::consts::FRAC_PI_2;
+
+/// Net of a hypercube: its facets hinged together at their ridges in a tree, so that they open
+/// out into n - 1 dimensions.
+///
+/// The facet where the last coordinate is negative stays in place, every other facet is hinged
+/// on it except the opposite one, which is hinged on the facet where the first coordinate is
+/// positive. The net of a cube is then the Latin cross, and that of a tesseract the Dalí cross.
+///
+/// Nets of 5-cubes and up can be unfolded recursively, down to 3 dimensions: once flat, each
+/// facet opens out into its own net with the same tree of hinges, and so on until only 3-cells
+/// are left. The facets are moved apart as they open so that their nets don't overlap, which
+/// cuts them apart along the ridges they shared, so the pieces no longer make up a net of the
+/// hypercube itself.
+#[derive(Debug, Clone)]
+pub struct NNet {
+    /// Number of dimensions of the hypercube
+    pub dimensions: usize,
+    /// Edge length
+    pub size: f64,
+    /// Cells the net is made of, its facets or its 3-cells, the one that stays in place first
+    pub cells: Vec<NCell>,
+    /// How each cell is brought into place, one fold per step of unfolding
+    folds: Vec<Vec<Fold>>,
+    /// Vertices of each cell in turn, where they are when the hypercube is folded
+    pub vertices: Vec<Vec<f64>>,
+    /// Cell of each vertex
+    pub vertex_cells: Vec<usize>,
+    /// Vertex of the hypercube that each vertex of the net comes from
+    pub origins: Vec<usize>,
+    /// Vertex indices of the edges of each cell
+    pub edges: NEdges,
+    /// Vertex indices of the 2D faces of each cell, in cyclic order
+    pub faces: NFaces,
+    /// Edge of the hypercube, by index in its edges, that each edge of the net comes from
+    pub edge_origins: Vec<usize>,
+    /// Face of the hypercube, by index in its faces, that each face of the net comes from
+    pub face_origins: Vec<usize>,
+}
+
+/// One step of unfolding of a cell, as part of a box that opens out into its facets
+#[derive(Debug, Clone)]
+struct Fold {
+    /// Center of the box, where it lies once the steps before are done
+    center: Vec<f64>,
+    /// Where the center of the box is moved to as it opens out
+    target: Vec<f64>,
+    /// Fixed dimension and side of the facet of the box that the cell lies in, then of each
+    /// facet it is hinged on in turn, up to the one that stays in place
+    chain: Vec<(usize, bool)>,
+}
+
+impl Fold {
+    /// Turns `point` by `theta_rad` about each hinge in turn, from folded at 0 to flat at a right
+    /// angle, moving it along with the box
+    fn apply(&self, point: &mut [f64], h: f64, theta_rad: f64) {
+        let sign = |side: bool| if side { 1.0 } else { -1.0 };
+        for link in self.chain.windows(2) {
+            let ((a, side_a), (b, side_b)) = (link[0], link[1]);
+            let (sa, sb) = (sign(side_a), sign(side_b));
+            let (sin, cos) = (sa * sb * theta_rad).sin_cos();
+            let (ra, rb) = (self.center[a] + sa * h, self.center[b] + sb * h);
+            let (u, v) = (point[a] - ra, point[b] - rb);
+            point[a] = ra + u * cos - v * sin;
+            point[b] = rb + u * sin + v * cos;
+        }
+        let t = theta_rad / FRAC_PI_2;
+        for (x, (c, target)) in point.iter_mut().zip(self.center.iter().zip(&self.target)) {
+            *x += t * (target - c);
+        }
+    }
+}
+
+/// Facets of a box spanning the first `m` axes, each with the one it is hinged on: every facet
+/// on the one where coordinate `m - 1` is negative, except the opposite one, which is hinged on
+/// the one where the first coordinate is positive
+fn hinge_tree(m: usize) -> Vec<((usize, bool), Option<usize>)> {
+    let mut tree = vec![((m - 1, false), None)];
+    for d in 0..m - 1 {
+        for side in [true, false] {
+            tree.push(((d, side), Some(0)));
+        }
+    }
+    tree.push(((m - 1, true), Some(1)));
+    tree
+}
+
+/// Where the `centers` of boxes spanning the first `m` axes are moved to as they open out: on a
+/// grid in the hyperplane where coordinate `m - 1` is 0, their rows along that axis laid end to
+/// end along axis `m - 2`, and far enough apart that their nets don't overlap
+fn layout(centers: &[Vec<f64>], m: usize, h: f64) -> Vec<Vec<f64>> {
+    let grid = |x: f64| (x / (2.0 * h)).round() as i64;
+    let (a, b) = (m - 2, m - 1);
+    let (min, max) = centers
+        .iter()
+        .map(|c| grid(c[a]))
+        .fold((i64::MAX, i64::MIN), |(min, max), g| {
+            (min.min(g), max.max(g))
+        });
+    centers
+        .iter()
+        .map(|c| {
+            let mut target = vec![0.0; c.len()];
+            for (d, x) in target.iter_mut().enumerate().take(b) {
+                let g = grid(c[d])
+                    + if d == a {
+                        (max - min + 1) * grid(c[b])
+                    } else {
+                        0
+                    };
+                // A net reaches a box past its own on each side, and two past on the first axis
+                let spacing = if d == 0 { 5.0 } else { 4.0 };
+                *x = spacing * 2.0 * h * g as f64;
+            }
+            target[b] = h;
+            target
+        })
+        .collect()
+}
+
+impl NNet {
+    /// Creates the net of an `n` dimensional hypercube of size `s`.
+    pub fn new(n: usize, s: f64) -> Self {
+        Self::unfolded(n, s, n - 1)
+    }
+
+    /// Creates the net of an `n` dimensional hypercube of size `s`, unfolded recursively down to
+    /// 3 dimensions.
+    pub fn recursive(n: usize, s: f64) -> Self {
+        Self::unfolded(n, s, 3.min(n - 1))
+    }
+
+    /// Checks whether the facets are unfolded any further than into `n - 1` dimensions
+    pub fn is_recursive(&self) -> bool {
+        self.cells[0].fixed.len() > 1
+    }
+
+    /// Unfolds the hypercube one dimension at a time, until its cells have `dimensions`
+    fn unfolded(n: usize, s: f64, dimensions: usize) -> Self {
+        let ncube = NCube::new(n, s);
+        let h = s / 2.0;
+        let steps = n - dimensions;
+        let placed = |i: usize, folds: &[Fold]| {
+            let mut point = ncube.vertices.0[i].clone();
+            folds
+                .iter()
+                .for_each(|fold| fold.apply(&mut point, h, FRAC_PI_2));
+            point
+        };
+        // Boxes left to open out, with the folds that brought them into place
+        let mut boxes = vec![(NCell { fixed: Vec::new() }, Vec::<Fold>::new())];
+        for step in 0..steps {
+            let m = n - step;
+            let tree = hinge_tree(m);
+            let centers = boxes
+                .iter()
+                .map(|(cell, folds)| {
+                    let vertices = (0..ncube.vertices.0.len()).filter(|i| cell.contains(*i));
+                    let count = vertices.clone().count() as f64;
+                    vertices.fold(vec![0.0; n], |mut center, i| {
+                        let point = placed(i, folds);
+                        center
+                            .iter_mut()
+                            .zip(point)
+                            .for_each(|(c, x)| *c += x / count);
+                        center
+                    })
+                })
+                .collect::<Vec<_>>();
+            // The net stays where it is unless it is unfolded any further
+            let targets = if steps > 1 {
+                layout(&centers, m, h)
+            } else {
+                centers.clone()
+            };
+            let mut next = Vec::new();
+            for ((cell, folds), (center, target)) in
+                boxes.iter().zip(centers.into_iter().zip(targets))
+            {
+                let vertices = (0..ncube.vertices.0.len())
+                    .filter(|i| cell.contains(*i))
+                    .map(|i| (i, placed(i, folds)))
+                    .collect::<Vec<_>>();
+                for (facet, &((axis, side), _)) in tree.iter().enumerate() {
+                    let ridge = center[axis] + if side { h } else { -h };
+                    let origins = vertices
+                        .iter()
+                        .filter(|(_, point)| (point[axis] - ridge).abs() < 1e-9)
+                        .map(|(i, _)| *i)
+                        .collect::<Vec<_>>();
+                    let mut chain = vec![(axis, side)];
+                    let mut parent = tree[facet].1;
+                    while let Some(p) = parent {
+                        chain.push(tree[p].0);
+                        parent = tree[p].1;
+                    }
+                    let mut folds = folds.clone();
+                    folds.push(Fold {
+                        center: center.clone(),
+                        target: target.clone(),
+                        chain,
+                    });
+                    next.push((NCell::spanned(&origins, n), folds));
+                }
+            }
+            boxes = next;
+        }
+
+        let mut net = Self {
+            dimensions: n,
+            size: s,
+            cells: Vec::new(),
+            folds: Vec::new(),
+            vertices: Vec::new(),
+            vertex_cells: Vec::new(),
+            origins: Vec::new(),
+            edges: NEdges(Vec::new()),
+            faces: NFaces(Vec::new()),
+            edge_origins: Vec::new(),
+            face_origins: Vec::new(),
+        };
+        for (c, (cell, folds)) in boxes.into_iter().enumerate() {
+            let offset = net.vertices.len();
+            let origins = (0..ncube.vertices.0.len())
+                .filter(|i| cell.contains(*i))
+                .collect::<Vec<_>>();
+            let index = |i: &usize| offset + origins.binary_search(i).unwrap();
+            for (e, (i, j)) in ncube.edges.0.iter().enumerate() {
+                if cell.contains(*i) && cell.contains(*j) {
+                    net.edges.0.push((index(i), index(j)));
+                    net.edge_origins.push(e);
+                }
+            }
+            for (k, face) in ncube.faces.0.iter().enumerate() {
+                if face.iter().all(|i| cell.contains(*i)) {
+                    net.faces.0.push(face.iter().map(index).collect());
+                    net.face_origins.push(k);
+                }
+            }
+            net.vertices
+                .extend(origins.iter().map(|i| ncube.vertices.0[*i].clone()));
+            net.vertex_cells.extend(origins.iter().map(|_| c));
+            net.origins.extend(origins);
+            net.cells.push(cell);
+            net.folds.push(folds);
+        }
+        net
+    }
+
+    /// Vertices of the net with each cell turned by `theta_rad` about its hinges, from folded at
+    /// 0 to flat at a right angle. A net unfolded recursively opens out one step after another.
+    pub fn unfold(&self, theta_rad: f64) -> Vec<Vec<f64>> {
+        let h = self.size / 2.0;
+        let steps = self.cells[0].fixed.len() as f64;
+        self.vertices
+            .iter()
+            .zip(&self.vertex_cells)
+            .map(|(vertex, cell)| {
+                let mut point = vertex.clone();
+                for (step, fold) in self.folds[*cell].iter().enumerate() {
+                    let t = (theta_rad / FRAC_PI_2 * steps - step as f64).clamp(0.0, 1.0);
+                    fold.apply(&mut point, h, t * FRAC_PI_2);
+                }
+                point
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn distinct(vertices: &[Vec<f64>]) -> usize {
+        let mut keys = vertices
+            .iter()
+            .map(|v| {
+                v.iter()
+                    .map(|x| (x * 1e6).round() as i64)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        keys.len()
+    }
+    #[test]
+    fn net_counts() {
+        let tesseract_net = NNet::new(4, 2.0);
+        assert_eq!(tesseract_net.cells.len(), 8);
+        assert_eq!(tesseract_net.vertices.len(), 8 * 8);
+        assert_eq!(tesseract_net.edges.0.len(), 8 * 12);
+        assert_eq!(tesseract_net.faces.0.len(), 8 * 6);
+        let ncube = NCube::new(4, 2.0);
+        for (e, (i, j)) in tesseract_net.edges.0.iter().enumerate() {
+            let origins = (tesseract_net.origins[*i], tesseract_net.origins[*j]);
+            assert_eq!(ncube.edges.0[tesseract_net.edge_origins[e]], origins);
+        }
+    }
+    #[test]
+    fn unfold_cross() {
+        for (n, cross_vertices) in [(3, 14), (4, 36)] {
+            let net = NNet::new(n, 2.0);
+            let folded = net.unfold(0.0);
+            assert_eq!(folded, net.vertices);
+            assert_eq!(distinct(&folded), 2_usize.pow(n as u32));
+
+            let unfolded = net.unfold(std::f64::consts::FRAC_PI_2);
+            assert!(unfolded.iter().all(|v| (v[n - 1] + 1.0).abs() < 1e-9));
+            assert_eq!(distinct(&unfolded), cross_vertices);
+        }
+    }
+    #[test]
+    fn unfold_recursively_into_3d() {
+        for (n, cells) in [(5, 10 * 8), (6, 12 * 10 * 8)] {
+            let net = NNet::recursive(n, 2.0);
+            assert!(net.is_recursive());
+            assert_eq!(net.cells.len(), cells);
+            assert!(net.cells.iter().all(|cell| cell.fixed.len() == n - 3));
+            assert_eq!(net.vertices.len(), cells * 8);
+            assert_eq!(net.edges.0.len(), cells * 12);
+            assert_eq!(net.faces.0.len(), cells * 6);
+            assert_eq!(net.unfold(0.0), net.vertices);
+
+            // Every cube flat in 3D, none of them overlapping
+            let unfolded = net.unfold(std::f64::consts::FRAC_PI_2);
+            assert!(unfolded
+                .iter()
+                .flat_map(|v| &v[3..])
+                .all(|x| x.abs() < 1e-9));
+            let centers = unfolded
+                .chunks(8)
+                .map(|cube| {
+                    (0..3)
+                        .map(|d| cube.iter().map(|v| v[d]).sum::<f64>() / 8.0)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            for (cube, center) in unfolded.chunks(8).zip(&centers) {
+                assert_eq!(distinct(cube), 8);
+                assert!(cube
+                    .iter()
+                    .all(|v| (0..3).all(|d| ((v[d] - center[d]).abs() - 1.0).abs() < 1e-9)));
+            }
+            for (i, a) in centers.iter().enumerate() {
+                for b in &centers[i + 1..] {
+                    assert!((0..3).any(|d| (a[d] - b[d]).abs() > 2.0 - 1e-9));
+                }
+            }
+        }
+        assert!(!NNet::recursive(4, 2.0).is_recursive());
+    }
+}
@@ -0,0 +1,177 @@
+use crate::ncube::{NCube, NEdges, NFaces, NVertices};
+
+/// Cross-section of a hypercube: the n - 1 dimensional polytope where the hyperplane through its
+/// center, across its last axis, cuts it in its current orientation.
+///
+/// Each vertex of the section is where an edge of the hypercube crosses the hyperplane, each
+/// edge where a 2D face crosses it and each 2D face where a 3D cell crosses it. A tesseract is
+/// cut into a cube at rest, and into other polyhedra as it turns out of the hyperplane.
+#[derive(Debug, Clone)]
+pub struct NSection {
+    /// Number of dimensions of the section, one less than those of the hypercube
+    pub dimensions: usize,
+    /// Edge length of the hypercube
+    pub size: f64,
+    /// Cartesian coordinates of the vertices, without the last axis of the hypercube
+    pub vertices: NVertices,
+    /// Edge of the hypercube, by index in its edges, that each vertex lies on
+    pub vertex_origins: Vec<usize>,
+    /// Vertex indices of the edges of the section
+    pub edges: NEdges,
+    /// Vertex indices of the 2D faces of the section, in cyclic order
+    pub faces: NFaces,
+}
+
+impl NSection {
+    /// Cuts `ncube` through its center, across its last axis
+    pub fn new(ncube: &NCube) -> Self {
+        let n = ncube.dimensions;
+        let side = |i: usize| ncube.vertices.0[i][n - 1] < 0.0;
+
+        let mut vertices = Vec::new();
+        let mut vertex_origins = Vec::new();
+        let mut vertex_of_edge = vec![None; ncube.edges.0.len()];
+        for (e, (i, j)) in ncube.edges.0.iter().enumerate() {
+            if side(*i) == side(*j) {
+                continue;
+            }
+            let (a, b) = (&ncube.vertices.0[*i], &ncube.vertices.0[*j]);
+            let t = a[n - 1] / (a[n - 1] - b[n - 1]);
+            vertex_of_edge[e] = Some(vertices.len());
+            vertices.push((0..n - 1).map(|d| a[d] + t * (b[d] - a[d])).collect());
+            vertex_origins.push(e);
+        }
+        let crossing = |polygon: &[usize]| -> Vec<usize> {
+            (0..polygon.len())
+                .filter_map(|k| {
+                    let (i, j) = (polygon[k], polygon[(k + 1) % polygon.len()]);
+                    let e = ncube
+                        .edges
+                        .0
+                        .iter()
+                        .position(|edge| *edge == (i.min(j), i.max(j)));
+                    e.and_then(|e| vertex_of_edge[e])
+                })
+                .collect()
+        };
+
+        // A convex face is crossed twice when it is crossed at all
+        let mut face_edges = Vec::new();
+        let mut edges = Vec::new();
+        for face in &ncube.faces.0 {
+            if let [a, b] = crossing(face)[..] {
+                face_edges.push((face, edges.len()));
+                edges.push((a.min(b), a.max(b)));
+            }
+        }
+
+        // The edges of the section within a cell make up a cycle
+        let mut faces = Vec::new();
+        for cell in ncube.cells(3) {
+            let mut cycle_edges: Vec<(usize, usize)> = face_edges
+                .iter()
+                .filter(|(face, _)| face.iter().all(|i| cell.contains(*i)))
+                .map(|(_, k)| edges[*k])
+                .collect();
+            let Some((first, mut next)) = cycle_edges.pop() else {
+                continue;
+            };
+            let mut face = vec![first];
+            while next != first {
+                face.push(next);
+                let Some(k) = cycle_edges
+                    .iter()
+                    .position(|(i, j)| *i == next || *j == next)
+                else {
+                    break;
+                };
+                let (i, j) = cycle_edges.swap_remove(k);
+                next = if i == next { j } else { i };
+            }
+            if face.len() > 2 {
+                faces.push(face);
+            }
+        }
+
+        Self {
+            dimensions: n - 1,
+            size: ncube.size,
+            vertices: NVertices(vertices),
+            vertex_origins,
+            edges: NEdges(edges),
+            faces: NFaces(faces),
+        }
+    }
+
+    /// Projects the vertices down to 3 dimensions with the perspective projection of
+    /// [`NCube::perspective_project`], the section of a cube lying flat in the third
+    pub fn perspective_project_vertices(&self) -> Vec<[f64; 3]> {
+        let points = self
+            .vertices
+            .0
+            .iter()
+            .map(|vertex| {
+                let mut vertex = vertex.clone();
+                vertex.resize(vertex.len().max(3), 0.0);
+                vertex
+            })
+            .collect::<Vec<_>>();
+        NCube::perspective_project_points(&points, self.dimensions.max(3), self.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExtendedMathOps;
+    #[test]
+    fn tesseract_section() {
+        let mut tesseract = NCube::new(4, 2.0);
+        // At rest, the tesseract is cut into a cube
+        let section = NSection::new(&tesseract);
+        assert_eq!(section.dimensions, 3);
+        assert_eq!(section.vertices.0.len(), 8);
+        assert_eq!(section.edges.0.len(), 12);
+        assert_eq!(section.faces.0.len(), 6);
+        assert!(section
+            .vertices
+            .0
+            .iter()
+            .all(|vertex| vertex.iter().all(|x| x.abs() == 1.0)));
+        for face in &section.faces.0 {
+            assert_eq!(face.len(), 4);
+            for k in 0..face.len() {
+                let (i, j) = (face[k], face[(k + 1) % face.len()]);
+                assert!(section.edges.0.contains(&(i.min(j), i.max(j))));
+            }
+        }
+        // Turned out of the hyperplane, it is cut into another polyhedron
+        let planes = usize::pair_permutations(0, 3);
+        let angles = planes
+            .iter()
+            .map(|plane| match plane {
+                (0, 3) => 0.3,
+                (1, 3) => 0.7,
+                (2, 3) => 1.1,
+                _ => 0.0,
+            })
+            .collect::<Vec<_>>();
+        tesseract.rotate(&planes, &angles);
+        let section = NSection::new(&tesseract);
+        let (v, e, f) = (
+            section.vertices.0.len() as i64,
+            section.edges.0.len() as i64,
+            section.faces.0.len() as i64,
+        );
+        assert_ne!(v, 8);
+        assert_eq!(v - e + f, 2);
+
+        let cube_section = NSection::new(&NCube::new(3, 2.0));
+        assert_eq!(cube_section.faces.0.len(), 1);
+        assert_eq!(cube_section.faces.0[0].len(), 4);
+        assert!(cube_section
+            .perspective_project_vertices()
+            .iter()
+            .all(|vertex| vertex[2] == 0.0));
+    }
+}
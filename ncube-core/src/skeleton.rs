@@ -0,0 +1,85 @@
+use crate::ncube::NCube;
+use std::collections::VecDeque;
+
+/// Skeleton of a hypercube: the graph of its vertices joined by its edges.
+#[derive(Debug, Clone)]
+pub struct NSkeleton {
+    /// Neighbors of each vertex
+    pub adjacency: Vec<Vec<usize>>,
+}
+
+impl NSkeleton {
+    /// Creates the skeleton of `ncube` from its vertices and edges.
+    pub fn new(ncube: &NCube) -> Self {
+        let mut adjacency = vec![Vec::new(); ncube.vertices.0.len()];
+        for (i, j) in &ncube.edges.0 {
+            adjacency[*i].push(*j);
+            adjacency[*j].push(*i);
+        }
+        Self { adjacency }
+    }
+
+    /// Number of edges at vertex `i`
+    pub fn degree(&self, i: usize) -> usize {
+        self.adjacency[i].len()
+    }
+
+    /// Number of edges on a shortest path from vertex `from` to each vertex, `None` for those
+    /// out of reach
+    pub fn distances(&self, from: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.adjacency.len()];
+        distances[from] = Some(0);
+        let mut queue = VecDeque::from([from]);
+        while let Some(i) = queue.pop_front() {
+            let d = distances[i].map(|d| d + 1);
+            for j in &self.adjacency[i] {
+                if distances[*j].is_none() {
+                    distances[*j] = d;
+                    queue.push_back(*j);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Vertex farthest from vertex `i`, the lowest one on a tie
+    pub fn antipode(&self, i: usize) -> usize {
+        let distances = self.distances(i);
+        (0..distances.len())
+            .max_by_key(|j| (distances[*j], std::cmp::Reverse(*j)))
+            .unwrap_or(i)
+    }
+
+    /// Greatest distance between two vertices, `None` if the skeleton is disconnected
+    pub fn diameter(&self) -> Option<usize> {
+        let mut diameter = 0;
+        for i in 0..self.adjacency.len() {
+            let distances = self.distances(i).into_iter().collect::<Option<Vec<_>>>()?;
+            diameter = distances.into_iter().fold(diameter, usize::max);
+        }
+        Some(diameter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn tesseract_skeleton() {
+        let skeleton = NSkeleton::new(&NCube::new(4, 2.0));
+        assert!((0..16).all(|i| skeleton.degree(i) == 4));
+        assert_eq!(skeleton.diameter(), Some(4));
+        assert_eq!(skeleton.antipode(0b0101), 0b1010);
+        let distances = skeleton.distances(0b0101);
+        assert!((0..16).all(|i| distances[i] == Some((i ^ 0b0101).count_ones() as usize)));
+    }
+    #[test]
+    fn disconnected_skeleton() {
+        // An edge and a lone vertex
+        let skeleton = NSkeleton {
+            adjacency: vec![vec![1], vec![0], Vec::new()],
+        };
+        assert_eq!(skeleton.distances(0), vec![Some(0), Some(1), None]);
+        assert_eq!(skeleton.diameter(), None);
+    }
+}
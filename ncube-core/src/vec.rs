@@ -0,0 +1,83 @@
+/// Operations on n-dimensional vectors
+pub trait MathOps {
+    /// Computes the dot product between 2 vectors
+    fn dot(&self, b: &Self) -> f64;
+    /// Computes the hadamard product between 2 vectors
+    fn hadamard(&self, b: &Self) -> Self;
+    /// Checks how many dimensions are shared across a group of `points` (equal in value).
+    /// Useful for checking whether the points lie within an n dimensional slice.
+    fn shared_dimensions(points: &[&Self]) -> Vec<usize>;
+    /// Computes the distance between 2 vectors
+    fn distance(&self, b: &Self) -> f64;
+    /// Computes the magnitude of a vector
+    fn magnitude(&self) -> f64;
+    /// Normalizes vector
+    fn normalize(&self) -> Self;
+    /// Scales vector
+    fn scale(&self, a: f64) -> Self;
+}
+
+impl MathOps for Vec<f64> {
+    fn dot(&self, b: &Self) -> f64 {
+        let len = self.len();
+        assert_eq!(len, b.len());
+        (0..len).map(|d| self[d] * b[d]).sum()
+    }
+
+    fn hadamard(&self, b: &Self) -> Self {
+        let len = self.len();
+        assert_eq!(len, b.len());
+        (0..len).map(|d| self[d] * b[d]).collect()
+    }
+
+    fn shared_dimensions(points: &[&Self]) -> Vec<usize> {
+        let mut common_d = Vec::new();
+        for d in 0..points[0].len() {
+            if points.iter().all(|p| p[d] == points[0][d]) {
+                common_d.push(d);
+            }
+        }
+        common_d
+    }
+
+    fn distance(&self, b: &Self) -> f64 {
+        let d = self.len();
+        assert_eq!(d, b.len());
+        self.iter()
+            .enumerate()
+            .map(|(i, x)| (b[i] - x).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    fn magnitude(&self) -> f64 {
+        self.iter().map(|x| x.powi(2)).sum::<f64>().sqrt()
+    }
+
+    fn normalize(&self) -> Self {
+        let l = self.magnitude();
+        self.iter().map(|x| x / l).collect()
+    }
+
+    fn scale(&self, a: f64) -> Self {
+        self.iter().map(|x| a * x).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn vec_ops() {
+        let a = vec![3.0, 0.0, 4.0];
+        let b = vec![1.0, 2.0, 0.0];
+        assert_eq!(a.dot(&b), 3.0);
+        assert_eq!(a.hadamard(&b), vec![3.0, 0.0, 0.0]);
+        assert_eq!(a.magnitude(), 5.0);
+        assert_eq!(a.normalize(), vec![0.6, 0.0, 0.8]);
+        assert_eq!(
+            Vec::shared_dimensions(&[&a, &vec![3.0, 1.0, 4.0]]),
+            vec![0, 2]
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Walks over the vertex graph of a hypercube, where vertex `i` is joined to every vertex that
+//! differs from it by a single bit.
+
+/// Vertices of an `n` dimensional hypercube in the order of the reflected binary Gray code, a
+/// Hamiltonian cycle that visits each of them once before closing on the first.
+pub fn gray_code(n: usize) -> Vec<usize> {
+    (0..1 << n).map(|i| i ^ (i >> 1)).collect()
+}
+
+/// Axes along which vertices `from` and `to` differ, in increasing order.
+pub fn differing_axes(from: usize, to: usize) -> Vec<usize> {
+    let diff = from ^ to;
+    (0..usize::BITS as usize)
+        .filter(|d| diff >> d & 1 == 1)
+        .collect()
+}
+
+/// Path from vertex `from` that flips the bits of `axes` one at a time, a shortest path to the
+/// vertex it ends on whenever `axes` has no repetitions.
+pub fn shortest_path(from: usize, axes: &[usize]) -> Vec<usize> {
+    let mut path = vec![from];
+    for d in axes {
+        path.push(path[path.len() - 1] ^ 1 << d);
+    }
+    path
+}
+
+/// Bits of vertex `i` of an `n` dimensional hypercube, the last axis first.
+pub fn bit_string(i: usize, n: usize) -> String {
+    format!("{i:0n$b}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn gray_code_cycle() {
+        let cycle = gray_code(4);
+        let mut sorted = cycle.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..16).collect::<Vec<_>>());
+        for (k, i) in cycle.iter().enumerate() {
+            let j = cycle[(k + 1) % cycle.len()];
+            assert_eq!((i ^ j).count_ones(), 1);
+        }
+        assert_eq!(bit_string(cycle[2], 4), "0011");
+    }
+    #[test]
+    fn shortest_paths() {
+        let axes = differing_axes(0b0101, 0b1100);
+        assert_eq!(axes, vec![0, 3]);
+        assert_eq!(shortest_path(0b0101, &axes), vec![0b0101, 0b0100, 0b1100]);
+        assert_eq!(shortest_path(0b0101, &[3, 0]), vec![0b0101, 0b1101, 0b1100]);
+    }
+}
@@ -0,0 +1,244 @@
+//! JavaScript API for pages that embed the web build. The hypercube settings apply to the
+//! selected hypercube.
+
+// Only the web build registers the plugin and exports the functions
+#![cfg_attr(not(target_family = "wasm"), allow(dead_code))]
+
+use bevy::prelude::*;
+use ncube::camera::{get_default_camera_projection, MainCamera};
+use ncube::data::{NCubeData, NCubeState};
+use ncube::hypercube::{HypercubeComponents, HypercubeDimension};
+use ncube::resources::{
+    ErrorToasts, PendingNCubeData, SelectedHypercube, MAX_DIMENSION, MIN_DIMENSION,
+};
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+
+pub struct ApiPlugin;
+
+impl Plugin for ApiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (apply_api_commands, publish_api_state).chain());
+    }
+}
+
+/// Change requested by the page, applied on the next update
+enum ApiCommand {
+    Dimension(usize),
+    Rotation((usize, usize), f64, f64),
+    EdgeColor(Color),
+    FaceColor(Color),
+    Paused(bool),
+    Camera(Vec3, bool),
+    State(Box<NCubeData>),
+}
+
+thread_local! {
+    /// Commands received since the last update
+    static COMMANDS: RefCell<Vec<ApiCommand>> = const { RefCell::new(Vec::new()) };
+    /// Current state as `NCubeData` JSON, without the vertices
+    static STATE: RefCell<Option<String>> = const { RefCell::new(None) };
+    static SUBSCRIBERS: RefCell<Vec<js_sys::Function>> = const { RefCell::new(Vec::new()) };
+}
+
+fn push_command(command: ApiCommand) {
+    COMMANDS.with_borrow_mut(|commands| commands.push(command));
+}
+
+#[wasm_bindgen(js_name = setDimension)]
+pub fn set_dimension(dimension: usize) -> Result<(), String> {
+    if !(MIN_DIMENSION..=MAX_DIMENSION).contains(&dimension) {
+        return Err(format!(
+            "dimension {dimension} is not between {MIN_DIMENSION} and {MAX_DIMENSION}"
+        ));
+    }
+    push_command(ApiCommand::Dimension(dimension));
+    Ok(())
+}
+
+/// Sets the angle and angular velocity of the plane of rotation `q1`-`q2`, in radians
+#[wasm_bindgen(js_name = setRotation)]
+pub fn set_rotation(q1: usize, q2: usize, angle: f64, velocity: f64) -> Result<(), String> {
+    if q1 == q2 {
+        return Err(format!("q{}q{} is not a plane", q1 + 1, q2 + 1));
+    }
+    if !angle.is_finite() || !velocity.is_finite() {
+        return Err(String::from("angle and velocity must be finite"));
+    }
+    push_command(ApiCommand::Rotation(
+        (q1.min(q2), q1.max(q2)),
+        angle % std::f64::consts::TAU,
+        velocity,
+    ));
+    Ok(())
+}
+
+/// Takes a hex color such as `"#00ffff"` or `"00ffff1a"`
+#[wasm_bindgen(js_name = setEdgeColor)]
+pub fn set_edge_color(hex: &str) -> Result<(), String> {
+    let color = Color::hex(hex).map_err(|e| e.to_string())?;
+    push_command(ApiCommand::EdgeColor(color));
+    Ok(())
+}
+
+/// Takes a hex color such as `"#00ffff"` or `"00ffff1a"`
+#[wasm_bindgen(js_name = setFaceColor)]
+pub fn set_face_color(hex: &str) -> Result<(), String> {
+    let color = Color::hex(hex).map_err(|e| e.to_string())?;
+    push_command(ApiCommand::FaceColor(color));
+    Ok(())
+}
+
+#[wasm_bindgen(js_name = setPaused)]
+pub fn set_paused(paused: bool) {
+    push_command(ApiCommand::Paused(paused));
+}
+
+/// Moves the camera to `(x, y, z)`, looking at the hypercube
+#[wasm_bindgen(js_name = setCamera)]
+pub fn set_camera(x: f32, y: f32, z: f32, orthographic: bool) -> Result<(), String> {
+    let translation = Vec3::new(x, y, z);
+    if !translation.is_finite() || translation == Vec3::ZERO {
+        return Err(String::from(
+            "camera position must be finite and not the origin",
+        ));
+    }
+    push_command(ApiCommand::Camera(translation, orthographic));
+    Ok(())
+}
+
+/// Replaces the whole scene with `NCubeData` JSON, the same as loading a data file
+#[wasm_bindgen(js_name = setState)]
+pub fn set_state(json: &str) -> Result<(), String> {
+    let data = NCubeData::parse(json).map_err(|e| e.to_string())?;
+    push_command(ApiCommand::State(Box::new(data)));
+    Ok(())
+}
+
+/// Current state as `NCubeData` JSON, without the vertices
+#[wasm_bindgen(js_name = getState)]
+pub fn get_state() -> Option<String> {
+    STATE.with_borrow(|state| state.clone())
+}
+
+/// Calls `callback` with the state JSON whenever a setting changes. The angles advancing
+/// while the rotation runs don't count as changes.
+#[wasm_bindgen]
+pub fn subscribe(callback: js_sys::Function) {
+    SUBSCRIBERS.with_borrow_mut(|subscribers| subscribers.push(callback));
+}
+
+fn apply_api_commands(
+    selected_hypercube: Res<SelectedHypercube>,
+    mut q_hypercubes: Query<HypercubeComponents>,
+    mut pending_ncube_data: ResMut<PendingNCubeData>,
+    mut error_toasts: ResMut<ErrorToasts>,
+    mut q_camera: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
+) {
+    let mut selected = selected_hypercube.and_then(|entity| q_hypercubes.get_mut(entity).ok());
+    for command in COMMANDS.take() {
+        match (command, &mut selected) {
+            (ApiCommand::Dimension(dimension), Some(hypercube)) => {
+                hypercube
+                    .dimension
+                    .set_if_neq(HypercubeDimension(dimension));
+            }
+            (ApiCommand::Rotation(plane, angle, vel), Some(hypercube)) => {
+                let n = **hypercube.dimension;
+                let Some(rotation) = hypercube.rotations.get_mut(&plane) else {
+                    error_toasts.push(format!(
+                        "q{}q{} is not a plane of the {n}-cube",
+                        plane.0 + 1,
+                        plane.1 + 1,
+                    ));
+                    continue;
+                };
+                // Turned from its current angle in the plane to the new one, and projected again
+                // by the hypercube systems
+                let delta = angle - rotation.0;
+                *rotation = (angle, vel);
+                if plane.1 < hypercube.hypercube.ncube.dimensions {
+                    hypercube.hypercube.ncube.rotate(&[plane], &[delta]);
+                }
+            }
+            (ApiCommand::EdgeColor(color), Some(hypercube)) => hypercube.style.edge_color = color,
+            (ApiCommand::FaceColor(color), Some(hypercube)) => hypercube.style.face_color = color,
+            (ApiCommand::Paused(paused), Some(hypercube)) => **hypercube.paused = paused,
+            (ApiCommand::Camera(translation, orthographic), _) => {
+                let (mut camera_transform, mut camera_projection) = q_camera.single_mut();
+                *camera_transform =
+                    Transform::from_translation(translation).looking_at(Vec3::ZERO, Vec3::Y);
+                *camera_projection =
+                    get_default_camera_projection(orthographic.then(|| translation.length()));
+            }
+            (ApiCommand::State(data), _) => **pending_ncube_data = Some(*data),
+            // Hypercube settings while there is no hypercube
+            _ => {}
+        }
+    }
+}
+
+fn publish_api_state(ncube_state: NCubeState, mut last_settings: Local<String>) {
+    // The last state stands until a hypercube flattened into a lower dimension is rebuilt
+    if ncube_state.is_rebuilding() {
+        return;
+    }
+    let mut data = ncube_state.snapshot();
+    data.hypercubes
+        .iter_mut()
+        .for_each(|hypercube| hypercube.vertices = None);
+    let Ok(state) = serde_json::to_string(&data) else {
+        return;
+    };
+    for hypercube in &mut data.hypercubes {
+        hypercube
+            .rotations
+            .iter_mut()
+            .for_each(|rotation| rotation.2 = 0.0);
+    }
+    let settings = serde_json::to_string(&data).unwrap_or_default();
+    if settings != *last_settings {
+        *last_settings = settings;
+        SUBSCRIBERS.with_borrow(|subscribers| {
+            for callback in subscribers {
+                callback
+                    .call1(&JsValue::NULL, &JsValue::from_str(&state))
+                    .ok();
+            }
+        });
+    }
+    STATE.set(Some(state));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::schedule::ExecutorKind;
+    use ncube::hypercube::{Hypercube, HypercubeBundle, HypercubeRotations};
+    use ncube::resources::ResourcesPlugin;
+    use std::f64::consts::FRAC_PI_2;
+    #[test]
+    fn set_rotation_turns_vertices() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ResourcesPlugin))
+            .add_systems(Update, apply_api_commands)
+            // The commands are kept on the thread they are pushed from
+            .edit_schedule(Update, |schedule| {
+                schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+            });
+        let entity = app.world.spawn(HypercubeBundle::new(3)).id();
+        **app.world.resource_mut::<SelectedHypercube>() = Some(entity);
+        let vertex = app.world.get::<Hypercube>(entity).unwrap().ncube.vertices.0[1].clone();
+
+        set_rotation(0, 1, FRAC_PI_2, 0.5).unwrap();
+        app.update();
+        let rotations = app.world.get::<HypercubeRotations>(entity).unwrap();
+        assert_eq!(rotations[&(0, 1)], (FRAC_PI_2, 0.5));
+        // A quarter turn in q1q2 takes (x, y, z) to (-y, x, z), or its reverse
+        let hypercube = app.world.get::<Hypercube>(entity).unwrap();
+        let turned = &hypercube.ncube.vertices.0[1];
+        assert!((turned[0].abs() - vertex[1].abs()).abs() < 1e-12);
+        assert!((turned[1].abs() - vertex[0].abs()).abs() < 1e-12);
+        assert_ne!(turned, &vertex);
+    }
+}
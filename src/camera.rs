@@ -1,5 +1,11 @@
-use crate::resources::SIZE;
-use crate::vec::{SphericalCoordinate, SphericalCoordinateSystem};
+use crate::hypercube::{Hypercube, HypercubeRotations, HypercubeUnfolding};
+use crate::resources::{
+    DragMomentum, DragPlanes, DragRotation, ResourcesPlugin, SelectedHypercube, SplitScreenViews,
+    SIZE,
+};
+use crate::vec::{SphericalCoordinate, SphericalCoordinateSystem, Vertices3D};
+use crate::views::ViewHypercube;
+use bevy::input::mouse::MouseMotion;
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
 use bevy::window::PrimaryWindow;
@@ -8,11 +14,18 @@ pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (spawn_camera, spawn_lighting))
-            .add_systems(Update, update_camera);
+        if !app.is_plugin_added::<ResourcesPlugin>() {
+            app.add_plugins(ResourcesPlugin);
+        }
+        app.add_systems(Startup, spawn_camera)
+            .add_systems(Update, (update_camera, drag_hypercube));
     }
 }
 
+/// Camera orbited with the mouse, which the settings and data files refer to
+#[derive(Component)]
+pub struct MainCamera;
+
 const WHEEL_FACTOR: f32 = if cfg!(target_family = "wasm") {
     0.001
 } else {
@@ -44,48 +57,30 @@ pub fn get_default_camera_projection(ortho_d: Option<f32>) -> Projection {
 }
 
 fn spawn_camera(mut commands: Commands) {
-    commands.spawn(Camera3dBundle {
-        transform: get_default_camera_transform(),
-        camera: Camera {
-            clear_color: bevy::render::camera::ClearColorConfig::Custom(Color::BLACK),
-            ..default()
-        },
-        camera_3d: Camera3d { ..default() },
-        projection: get_default_camera_projection(None),
-        ..default()
-    });
-}
-
-fn spawn_lighting(mut commands: Commands) {
-    let intensity = 1500000.0 * SIZE.powi(2);
-    let range = 20.0 * SIZE;
-    commands.spawn(PointLightBundle {
-        point_light: PointLight {
-            intensity,
-            range,
-            ..default()
-        },
-        transform: Transform::from_translation(Vec3::new(3.0, 8.0, 4.0) * SIZE),
-        ..default()
-    });
-    commands.spawn(PointLightBundle {
-        point_light: PointLight {
-            intensity,
-            range,
+    commands.spawn((
+        Camera3dBundle {
+            transform: get_default_camera_transform(),
+            camera: Camera {
+                clear_color: bevy::render::camera::ClearColorConfig::Custom(Color::BLACK),
+                ..default()
+            },
+            camera_3d: Camera3d { ..default() },
+            projection: get_default_camera_projection(None),
             ..default()
         },
-        transform: Transform::from_translation(Vec3::new(-4.0, 8.0, -4.0) * SIZE),
-        ..default()
-    });
+        MainCamera,
+        IsDefaultUiCamera,
+    ));
 }
 
 fn update_camera(
-    mut q_camera: Query<(&mut Transform, &mut Projection), With<Camera>>,
+    mut q_camera: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
     mut q_primary_window: Query<&mut Window, With<PrimaryWindow>>,
     mut mouse_wheel_events: EventReader<bevy::input::mouse::MouseWheel>,
     mut mouse_motion_events: EventReader<bevy::input::mouse::MouseMotion>,
     mouse_button_input: Res<ButtonInput<MouseButton>>,
     keys: Res<ButtonInput<KeyCode>>,
+    drag_rotation: Res<DragRotation>,
 ) {
     let (mut camera_transform, mut camera_projection) = q_camera.get_single_mut().unwrap();
     let mut window = q_primary_window.get_single_mut().unwrap();
@@ -119,6 +114,9 @@ fn update_camera(
     }
 
     window.cursor.visible = false;
+    if **drag_rotation {
+        return;
+    }
 
     mouse_motion_events.read().for_each(|event| {
         if event.delta.length() == 0.0 {
@@ -128,10 +126,155 @@ fn update_camera(
             curr_pos_spherical.r,
             (curr_pos_spherical.theta + event.delta.y.to_radians() * -MOTION_FACTOR)
                 // Prevent flipping effect
-                .min(std::f32::consts::PI * (1.0 - std::f32::EPSILON))
-                .max(std::f32::EPSILON),
+                .clamp(f32::EPSILON, std::f32::consts::PI * (1.0 - f32::EPSILON)),
             curr_pos_spherical.phi + event.delta.x.to_radians() * -MOTION_FACTOR,
         ));
         *camera_transform = camera_transform.looking_at(Vec3::ZERO, Vec3::Y);
     });
 }
+
+/// Weight of the latest frame in the speed a hypercube is dragged at
+const DRAG_SMOOTHING: f64 = 0.5;
+
+/// Turns the selected hypercube in the drag planes, like a trackball, while the right mouse
+/// button is held. Shift raises the second dimension of the planes by one and alt by two, up to
+/// the last dimension. In a split screen the other hypercubes, which turn along with the
+/// selected one, are turned in the planes they have too.
+#[allow(clippy::too_many_arguments)]
+fn drag_hypercube(
+    time: Res<Time>,
+    drag_rotation: Res<DragRotation>,
+    drag_planes: Res<DragPlanes>,
+    drag_momentum: Res<DragMomentum>,
+    selected_hypercube: Res<SelectedHypercube>,
+    split_screen_views: Res<SplitScreenViews>,
+    mut q_hypercubes: Query<
+        (
+            Entity,
+            &mut Hypercube,
+            &mut HypercubeRotations,
+            &HypercubeUnfolding,
+        ),
+        Without<ViewHypercube>,
+    >,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut velocities: Local<Vec<((usize, usize), f64)>>,
+) {
+    let delta: Vec2 = mouse_motion_events.read().map(|event| event.delta).sum();
+    let selected = selected_hypercube.and_then(|entity| q_hypercubes.get_mut(entity).ok());
+    let Some((selected, hypercube, mut rotations, unfolding)) = selected else {
+        velocities.clear();
+        return;
+    };
+    if !**drag_rotation || unfolding.enabled {
+        velocities.clear();
+        return;
+    }
+    if mouse_button_input.just_released(MouseButton::Right) {
+        for (plane, vel) in velocities.drain(..) {
+            if let Some(rotation) = rotations.get_mut(&plane).filter(|_| **drag_momentum) {
+                rotation.1 = vel;
+            }
+        }
+        return;
+    }
+    if !mouse_button_input.pressed(MouseButton::Right) {
+        return;
+    }
+
+    let n = hypercube.ncube.dimensions;
+    let offset = usize::from(keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]))
+        + 2 * usize::from(keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]));
+    let dt = time.delta_seconds_f64();
+    let mut planes = Vec::new();
+    let mut angles = Vec::new();
+    for ((i, j), motion) in drag_planes.iter().zip([delta.x, delta.y]) {
+        let plane = (*i, (j + offset).min(n - 1));
+        if plane.0 >= plane.1 || planes.contains(&plane) {
+            continue;
+        }
+        let angle = f64::from((motion * MOTION_FACTOR).to_radians());
+        let vel = if dt > 0.0 { angle / dt } else { 0.0 };
+        let smoothed = velocities
+            .iter()
+            .find(|(p, _)| *p == plane)
+            .map_or(vel, |(_, v)| v + DRAG_SMOOTHING * (vel - v));
+        planes.push(plane);
+        angles.push(angle);
+        velocities.retain(|(p, _)| *p != plane);
+        velocities.push((plane, smoothed));
+    }
+    velocities.retain(|(p, _)| planes.contains(p));
+    if angles.iter().all(|angle| *angle == 0.0) {
+        return;
+    }
+
+    let is_synced = **split_screen_views > 1;
+    for (entity, mut hypercube, mut rotations, _) in &mut q_hypercubes {
+        if entity != selected && !is_synced {
+            continue;
+        }
+        let n = hypercube.ncube.dimensions;
+        let (planes, angles): (Vec<_>, Vec<_>) = planes
+            .iter()
+            .zip(&angles)
+            .filter(|(plane, _)| plane.1 < n)
+            .map(|(plane, angle)| (*plane, *angle))
+            .unzip();
+        let hypercube = &mut *hypercube;
+        hypercube.ncube.rotate(&planes, &angles);
+        hypercube.vertices_3d = hypercube.ncube.vertices_3d();
+        for (plane, angle) in planes.iter().zip(angles) {
+            if let Some(rotation) = rotations.get_mut(plane) {
+                rotation.0 = (rotation.0 + angle) % std::f64::consts::TAU;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hypercube::HypercubeBundle;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+    #[test]
+    fn drag_with_momentum() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ResourcesPlugin))
+            .add_event::<MouseMotion>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .add_systems(Update, drag_hypercube);
+        **app.world.resource_mut::<DragRotation>() = true;
+        **app.world.resource_mut::<DragMomentum>() = true;
+        let entity = app.world.spawn(HypercubeBundle::new(5)).id();
+        app.update();
+
+        let ncube = |app: &App| app.world.get::<Hypercube>(entity).unwrap().ncube.clone();
+        let rotations = |app: &App| app.world.get::<HypercubeRotations>(entity).unwrap().clone();
+        let vertices = ncube(&app).vertices.0;
+        app.world
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .press(MouseButton::Right);
+        app.world.send_event(MouseMotion {
+            delta: Vec2::new(10.0, 0.0),
+        });
+        app.update();
+        assert_eq!(rotations(&app)[&(0, 3)].0, f64::from(3.0_f32.to_radians()));
+        assert_eq!(rotations(&app)[&(1, 3)].0, 0.0);
+        assert_ne!(ncube(&app).vertices.0, vertices);
+
+        let mut mouse_button_input = app.world.resource_mut::<ButtonInput<MouseButton>>();
+        mouse_button_input.clear();
+        mouse_button_input.release(MouseButton::Right);
+        app.update();
+        assert!(rotations(&app)[&(0, 3)].1 > 0.0);
+        assert_eq!(rotations(&app)[&(1, 3)].1, 0.0);
+    }
+}
@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use ncube::data::{HypercubeData, NCubeData};
+use ncube::resources::{
+    ErrorToasts, PendingNCubeData, ShowUi, DEFAULT_DIMENSION, MAX_DIMENSION, MIN_DIMENSION,
+};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: ncube [OPTIONS]
+
+Options:
+  --dimension <N>       dimension of the hypercube
+  --load <FILE>         load a data file
+  --paused              start with the rotation paused
+  --fresh               start without restoring the last session
+  --fullscreen          start in fullscreen
+  --size <WxH>          window size in logical pixels
+  --no-ui               hide the settings and controls windows
+  --speed <FACTOR>      multiply all angular velocities
+  --data-file-schema    print the JSON Schema of data files
+  --version             print the version
+  --help                print this help";
+
+/// Options given on the command line
+#[derive(Debug, Default, PartialEq)]
+pub struct Cli {
+    pub dimension: Option<usize>,
+    #[cfg_attr(target_family = "wasm", allow(dead_code))]
+    pub load: Option<PathBuf>,
+    pub paused: bool,
+    pub fresh: bool,
+    pub fullscreen: bool,
+    pub size: Option<(f32, f32)>,
+    pub no_ui: bool,
+    pub speed: Option<f64>,
+    pub data_file_schema: bool,
+    pub version: bool,
+    pub help: bool,
+}
+
+impl Cli {
+    /// Parses the arguments following the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{arg} expects a value"));
+            match arg.as_str() {
+                "--dimension" => {
+                    let value = value()?;
+                    let dimension = value
+                        .parse()
+                        .ok()
+                        .filter(|d| (MIN_DIMENSION..=MAX_DIMENSION).contains(d));
+                    cli.dimension = Some(dimension.ok_or(format!(
+                        "invalid dimension {value}, expected {MIN_DIMENSION} to {MAX_DIMENSION}"
+                    ))?);
+                }
+                "--load" => cli.load = Some(PathBuf::from(value()?)),
+                "--paused" => cli.paused = true,
+                "--fresh" => cli.fresh = true,
+                "--fullscreen" => cli.fullscreen = true,
+                "--size" => {
+                    let value = value()?;
+                    let size = value
+                        .split_once('x')
+                        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                        .filter(|(w, h): &(f32, f32)| *w > 0.0 && *h > 0.0);
+                    cli.size = Some(size.ok_or(format!("invalid size {value}, expected WxH"))?);
+                }
+                "--no-ui" => cli.no_ui = true,
+                "--speed" => {
+                    let value = value()?;
+                    let speed = value.parse::<f64>().ok().filter(|speed| speed.is_finite());
+                    cli.speed = Some(speed.ok_or(format!("invalid speed {value}"))?);
+                }
+                "--data-file-schema" => cli.data_file_schema = true,
+                "--version" => cli.version = true,
+                "--help" | "-h" => cli.help = true,
+                _ => return Err(format!("unknown argument {arg}")),
+            }
+        }
+        if cli.dimension.is_some() && cli.load.is_some() {
+            return Err("--dimension and --load can't be given together".to_string());
+        }
+        Ok(cli)
+    }
+
+    /// Overrides the initial resources with the given options, and gives the scene to start
+    /// with
+    pub fn insert_resources(&self, app: &mut App) {
+        if self.no_ui {
+            app.insert_resource(ShowUi(false));
+        }
+
+        #[cfg(not(target_family = "wasm"))]
+        let loaded = self.load.as_deref().map(NCubeData::read);
+        #[cfg(target_family = "wasm")]
+        let loaded: Option<Result<NCubeData, ncube::data::DataFileError>> = None;
+        // An explicit dimension asks for a new hypercube rather than the last session
+        let data = match loaded {
+            Some(Ok(data)) => Some(data),
+            Some(Err(e)) => {
+                app.insert_resource(ErrorToasts(vec![e.to_string()]));
+                None
+            }
+            None if self.fresh || self.dimension.is_some() => None,
+            None => ncube::session::load(),
+        };
+        let mut data = data.unwrap_or_else(|| NCubeData {
+            hypercubes: vec![HypercubeData::new(
+                self.dimension.unwrap_or(DEFAULT_DIMENSION),
+            )],
+            ..NCubeData::default()
+        });
+        let speed = self.speed.unwrap_or(1.0);
+        for hypercube in &mut data.hypercubes {
+            hypercube
+                .rotations
+                .iter_mut()
+                .for_each(|rotation| rotation.3 *= speed);
+            // Only the hypercubes started with, not those loaded later on
+            hypercube.paused |= self.paused;
+        }
+        data.fullscreen |= self.fullscreen;
+        app.insert_resource(PendingNCubeData(Some(data)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn parse(args: &[&str]) -> Result<Cli, String> {
+        Cli::parse(args.iter().map(|arg| arg.to_string()))
+    }
+    #[test]
+    fn parse_args() {
+        assert_eq!(parse(&[]), Ok(Cli::default()));
+        assert_eq!(
+            parse(&[
+                "--dimension",
+                "6",
+                "--paused",
+                "--fresh",
+                "--size",
+                "800x600",
+                "--speed",
+                "0.5",
+                "--no-ui",
+            ]),
+            Ok(Cli {
+                dimension: Some(6),
+                paused: true,
+                fresh: true,
+                size: Some((800.0, 600.0)),
+                no_ui: true,
+                speed: Some(0.5),
+                ..Cli::default()
+            })
+        );
+        assert!(parse(&["--dimension"]).is_err());
+        assert!(parse(&["--dimension", "12"]).is_err());
+        assert!(parse(&["--size", "800"]).is_err());
+        assert!(parse(&["--speed", "fast"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+    #[test]
+    fn parse_load() {
+        assert_eq!(
+            parse(&["--load", "demo.data"]),
+            Ok(Cli {
+                load: Some(PathBuf::from("demo.data")),
+                ..Cli::default()
+            })
+        );
+        // The dimension would be ignored
+        assert!(parse(&["--dimension", "6", "--load", "demo.data"]).is_err());
+        assert!(parse(&["--load", "demo.data", "--dimension", "6"]).is_err());
+    }
+}
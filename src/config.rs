@@ -0,0 +1,39 @@
+use std::path::{Path, PathBuf};
+
+const MAX_RECENT_FILES: usize = 10;
+
+/// Directory where the app persists its state between sessions
+pub fn config_dir() -> Option<PathBuf> {
+    home::home_dir().map(|home| home.join(".config").join(env!("CARGO_PKG_NAME")))
+}
+
+fn recent_files_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("recent-files.json"))
+}
+
+pub fn load_recent_files() -> Vec<PathBuf> {
+    recent_files_path()
+        .and_then(|path| std::fs::File::open(path).ok())
+        .and_then(|file| serde_json::from_reader(std::io::BufReader::new(file)).ok())
+        .unwrap_or_default()
+}
+
+fn save_recent_files(recent_files: &[PathBuf]) {
+    let Some(path) = recent_files_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).unwrap_or(());
+    }
+    if let Ok(file) = std::fs::File::create(path) {
+        serde_json::to_writer_pretty(file, recent_files).unwrap_or(());
+    }
+}
+
+/// Moves `path` to the top of the recent files and persists them
+pub fn push_recent_file(recent_files: &mut Vec<PathBuf>, path: &Path) {
+    recent_files.retain(|p| p != path);
+    recent_files.insert(0, path.to_path_buf());
+    recent_files.truncate(MAX_RECENT_FILES);
+    save_recent_files(recent_files);
+}
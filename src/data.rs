@@ -0,0 +1,947 @@
+use crate::camera::{get_default_camera_projection, get_default_camera_transform, MainCamera};
+use crate::hypercube::{
+    Hypercube, HypercubeBundle, HypercubeComponents, HypercubeComponentsItem,
+    HypercubeComponentsReadOnlyItem, HypercubeDimension, HypercubeExtrusion, HypercubeHighlight,
+    HypercubePaused, HypercubeRotations, HypercubeStyle, HypercubeUnfolding, HypercubeWalk,
+};
+use crate::impl_default;
+use crate::resources::{
+    default_scene_lights, Background, FaceBlendMode, HypercubeOrder, LoadPaused, PendingNCubeData,
+    SceneAmbientLight, SceneBackground, SceneLight, SceneLights, SceneShowAxes, SceneShowGrid,
+    SelectedHypercube, ShowInfoText, DEFAULT_AMBIENT_LIGHT, DEFAULT_DIMENSION, MAX_DIMENSION,
+    MIN_DIMENSION, SIZE,
+};
+use crate::vec::Vertices3D;
+use crate::views::ArrangedHypercube;
+use base64::Engine;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowMode};
+use ncube_core::ExtendedMathOps;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+pub struct DataPlugin;
+
+impl Plugin for DataPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_pending_ncube_data);
+    }
+}
+
+/// Version of the data file layout written by this build
+pub const DATA_FILE_VERSION: u32 = 3;
+
+/// Last version of the data file layout with a single hypercube
+const LEGACY_VERSION: u32 = 2;
+
+/// Largest decompressed share string accepted, in bytes
+const MAX_SHARE_SIZE: usize = 1 << 20;
+
+/// Upgrades a data file with a single hypercube from the version at its index to the next one,
+/// before it is converted to the current layout
+const MIGRATIONS: [fn(&mut LegacyData, &Header); LEGACY_VERSION as usize] =
+    [migrate_v0, migrate_v1];
+
+/// Fields read before the rest of a data file, to know how to interpret it
+#[derive(serde::Deserialize)]
+struct Header {
+    #[serde(default)]
+    version: u32,
+    /// Older layouts gave a missing pause state another meaning
+    paused: Option<bool>,
+}
+
+/// Unversioned files could store angles beyond a full turn
+fn migrate_v0(data: &mut LegacyData, _header: &Header) {
+    for (_, _, angle, _) in &mut data.rotations {
+        *angle %= std::f64::consts::TAU;
+    }
+}
+
+/// Files without a pause state were always loaded paused
+fn migrate_v1(data: &mut LegacyData, header: &Header) {
+    if header.paused.is_none() {
+        data.paused = true;
+    }
+}
+
+/// Mirrors how [`Color`] is serialized, for the data file schema
+#[derive(schemars::JsonSchema)]
+#[schemars(rename = "Color")]
+#[allow(dead_code)]
+pub enum ColorSchema {
+    Rgba {
+        red: f32,
+        green: f32,
+        blue: f32,
+        alpha: f32,
+    },
+    RgbaLinear {
+        red: f32,
+        green: f32,
+        blue: f32,
+        alpha: f32,
+    },
+    Hsla {
+        hue: f32,
+        saturation: f32,
+        lightness: f32,
+        alpha: f32,
+    },
+    Lcha {
+        lightness: f32,
+        chroma: f32,
+        hue: f32,
+        alpha: f32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct CameraTransform {
+    #[schemars(with = "[f32; 3]")]
+    pub translation: Vec3,
+    /// Quaternion as `[x, y, z, w]`
+    #[schemars(with = "[f32; 4]")]
+    pub rotation: Quat,
+    #[schemars(with = "[f32; 3]")]
+    pub scale: Vec3,
+}
+impl_default!(CameraTransform => {
+    let transform = get_default_camera_transform();
+    Self {
+        translation: transform.translation,
+        rotation: transform.rotation,
+        scale: transform.scale,
+    }
+});
+
+/// Description of a data file shown in the gallery
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct PresetMetadata {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+}
+
+/// Hypercube saved in a data file
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct HypercubeData {
+    pub dimension: usize,
+    /// Planes of rotation as `[axis, axis, angle, angular velocity]`, in radians
+    pub rotations: Vec<(usize, usize, f64, f64)>,
+    #[serde(default)]
+    pub paused: bool,
+    /// Edge length of the hypercube
+    #[serde(default = "default_size")]
+    pub size: f64,
+    /// Coordinates of the rotated vertices, since rotations in different planes don't
+    /// commute and the angles alone can't reproduce them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vertices: Option<Vec<Vec<f64>>>,
+    #[serde(default)]
+    pub edge_thickness: f32,
+    #[serde(default)]
+    #[schemars(with = "ColorSchema")]
+    pub edge_color: Color,
+    #[serde(default)]
+    #[schemars(with = "ColorSchema")]
+    pub face_color: Color,
+    #[serde(default)]
+    pub unlit: bool,
+    #[serde(default)]
+    pub face_color_by_plane: bool,
+    #[serde(default)]
+    pub isolate_face_planes: bool,
+    #[serde(default)]
+    pub selected_face_planes: Vec<(usize, usize)>,
+    #[serde(default)]
+    pub face_blend_mode: FaceBlendMode,
+}
+
+impl_default!(HypercubeData => Self::new(DEFAULT_DIMENSION));
+
+/// Scene saved in a data file
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct NCubeData {
+    /// Layout version, files without it are from before versioning
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<PresetMetadata>,
+    /// Hypercubes in the order they are listed and shown in the views
+    pub hypercubes: Vec<HypercubeData>,
+    /// Index of the hypercube that the settings work on
+    #[serde(default)]
+    pub selected: usize,
+    #[serde(default)]
+    pub camera_transform: CameraTransform,
+    #[serde(default)]
+    pub orthographic_projection: bool,
+    #[serde(default)]
+    pub background: Background,
+    #[serde(default = "default_scene_lights")]
+    pub lights: Vec<SceneLight>,
+    #[serde(default = "default_ambient_light")]
+    pub ambient_light: f32,
+    #[serde(default)]
+    pub show_grid: bool,
+    #[serde(default)]
+    pub show_axes: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+    #[serde(default = "default_show_info_text")]
+    pub show_info_text: bool,
+}
+
+impl_default!(NCubeData => Self {
+    version: DATA_FILE_VERSION,
+    metadata: None,
+    hypercubes: vec![HypercubeData::default()],
+    selected: 0,
+    camera_transform: CameraTransform::default(),
+    orthographic_projection: false,
+    background: Background::default(),
+    lights: default_scene_lights(),
+    ambient_light: default_ambient_light(),
+    show_grid: *SceneShowGrid::default(),
+    show_axes: *SceneShowAxes::default(),
+    fullscreen: false,
+    show_info_text: *ShowInfoText::default(),
+});
+
+/// Layout of the data files up to version 2, which held a single hypercube
+#[derive(serde::Deserialize)]
+struct LegacyData {
+    #[serde(default)]
+    metadata: Option<PresetMetadata>,
+    dimension: usize,
+    rotations: Vec<(usize, usize, f64, f64)>,
+    #[serde(default)]
+    paused: bool,
+    #[serde(default = "default_size")]
+    size: f64,
+    #[serde(default)]
+    vertices: Option<Vec<Vec<f64>>>,
+    #[serde(default)]
+    camera_transform: CameraTransform,
+    #[serde(default)]
+    orthographic_projection: bool,
+    #[serde(default)]
+    edge_thickness: f32,
+    #[serde(default)]
+    edge_color: Color,
+    #[serde(default)]
+    face_color: Color,
+    #[serde(default)]
+    unlit: bool,
+    #[serde(default)]
+    face_color_by_plane: bool,
+    #[serde(default)]
+    isolate_face_planes: bool,
+    #[serde(default)]
+    selected_face_planes: Vec<(usize, usize)>,
+    #[serde(default)]
+    face_blend_mode: FaceBlendMode,
+    #[serde(default)]
+    background: Background,
+    #[serde(default = "default_scene_lights")]
+    lights: Vec<SceneLight>,
+    #[serde(default = "default_ambient_light")]
+    ambient_light: f32,
+    #[serde(default)]
+    show_grid: bool,
+    #[serde(default)]
+    show_axes: bool,
+    #[serde(default)]
+    fullscreen: bool,
+    #[serde(default = "default_show_info_text")]
+    show_info_text: bool,
+}
+
+impl From<LegacyData> for NCubeData {
+    fn from(data: LegacyData) -> Self {
+        Self {
+            version: DATA_FILE_VERSION,
+            metadata: data.metadata,
+            hypercubes: vec![HypercubeData {
+                dimension: data.dimension,
+                rotations: data.rotations,
+                paused: data.paused,
+                size: data.size,
+                vertices: data.vertices,
+                edge_thickness: data.edge_thickness,
+                edge_color: data.edge_color,
+                face_color: data.face_color,
+                unlit: data.unlit,
+                face_color_by_plane: data.face_color_by_plane,
+                isolate_face_planes: data.isolate_face_planes,
+                selected_face_planes: data.selected_face_planes,
+                face_blend_mode: data.face_blend_mode,
+            }],
+            selected: 0,
+            camera_transform: data.camera_transform,
+            orthographic_projection: data.orthographic_projection,
+            background: data.background,
+            lights: data.lights,
+            ambient_light: data.ambient_light,
+            show_grid: data.show_grid,
+            show_axes: data.show_axes,
+            fullscreen: data.fullscreen,
+            show_info_text: data.show_info_text,
+        }
+    }
+}
+
+fn default_size() -> f64 {
+    SIZE.into()
+}
+
+fn default_ambient_light() -> f32 {
+    DEFAULT_AMBIENT_LIGHT
+}
+
+fn default_show_info_text() -> bool {
+    true
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DataFileError {
+    /// The data file could not be read
+    Io(String),
+    /// The data file is not JSON or does not have the expected fields
+    Parse {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+    /// The data file describes a scene that cannot be shown
+    Invalid(String),
+}
+
+impl std::fmt::Display for DataFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(message) => write!(f, "could not read data file: {message}"),
+            Self::Parse {
+                message,
+                line,
+                column,
+            } => write!(
+                f,
+                "data file error at line {line}, column {column}: {message}"
+            ),
+            Self::Invalid(message) => write!(f, "invalid data file: {message}"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for DataFileError {
+    fn from(e: serde_json::Error) -> Self {
+        let message = e.to_string();
+        // The location is reported separately
+        let message = match message.rfind(" at line ") {
+            Some(i) => message[..i].to_string(),
+            None => message,
+        };
+        Self::Parse {
+            message,
+            line: e.line(),
+            column: e.column(),
+        }
+    }
+}
+
+impl NCubeData {
+    /// Parses, migrates and validates the contents of a data file
+    pub fn parse(data: &str) -> Result<Self, DataFileError> {
+        let header = serde_json::from_str::<Header>(data)?;
+        let version = header.version;
+        if version > DATA_FILE_VERSION {
+            return Err(DataFileError::Invalid(format!(
+                "version {version} is newer than the supported version {DATA_FILE_VERSION}"
+            )));
+        }
+        // Older layouts are read straight from the text, so that their errors keep their
+        // positions
+        let data = if version <= LEGACY_VERSION {
+            let mut data = serde_json::from_str::<LegacyData>(data)?;
+            for migrate in &MIGRATIONS[version as usize..] {
+                migrate(&mut data, &header);
+            }
+            Self::from(data)
+        } else {
+            serde_json::from_str::<Self>(data)?
+        };
+        data.validate()?;
+        Ok(data)
+    }
+
+    /// Encodes the data as compressed, URL safe base64 for the fragment of a share link.
+    /// The vertices are left out to keep links short, the angles reproduce them closely.
+    #[cfg_attr(not(target_family = "wasm"), allow(dead_code))]
+    pub fn to_share_string(&self) -> String {
+        let mut data = self.clone();
+        data.hypercubes
+            .iter_mut()
+            .for_each(|hypercube| hypercube.vertices = None);
+        let value = serde_json::to_value(data).unwrap_or_default();
+        let compressed = miniz_oxide::deflate::compress_to_vec(value.to_string().as_bytes(), 9);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(compressed)
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), allow(dead_code))]
+    pub fn from_share_string(share_string: &str) -> Result<Self, DataFileError> {
+        let corrupted = || DataFileError::Invalid(String::from("share string is corrupted"));
+        let compressed = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(share_string.trim_start_matches('#'))
+            .map_err(|_| DataFileError::Invalid(String::from("share string is not base64")))?;
+        let data = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_SHARE_SIZE)
+            .map_err(|_| corrupted())?;
+        Self::parse(&String::from_utf8(data).map_err(|_| corrupted())?)
+    }
+
+    /// JSON Schema of the current data file layout
+    pub fn schema() -> String {
+        serde_json::to_string_pretty(&schemars::schema_for!(Self)).unwrap_or_default()
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read(path: &std::path::Path) -> Result<Self, DataFileError> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| DataFileError::Io(format!("{}: {e}", path.display())))?;
+        Self::parse(&data)
+    }
+
+    /// Checks that the data describes a scene that can be shown
+    pub fn validate(&self) -> Result<(), DataFileError> {
+        let invalid = |message: String| Err(DataFileError::Invalid(message));
+        if self.hypercubes.is_empty() {
+            return invalid(String::from("there are no hypercubes"));
+        }
+        if self.selected >= self.hypercubes.len() {
+            return invalid(format!(
+                "selected hypercube {} is not one of the {}",
+                self.selected + 1,
+                self.hypercubes.len()
+            ));
+        }
+        for (i, hypercube) in self.hypercubes.iter().enumerate() {
+            hypercube.validate().map_err(|message| {
+                DataFileError::Invalid(format!("hypercube {}: {message}", i + 1))
+            })?;
+        }
+
+        let camera_transform = &self.camera_transform;
+        let colors = self
+            .lights
+            .iter()
+            .map(|light| light.color)
+            .chain(match self.background {
+                Background::Color(color) => vec![color],
+                Background::Gradient(top, bottom) => vec![top, bottom],
+            });
+        let is_finite = camera_transform.translation.is_finite()
+            && camera_transform.rotation.is_finite()
+            && camera_transform.scale.is_finite()
+            && self.ambient_light.is_finite()
+            && self
+                .lights
+                .iter()
+                .all(|light| light.position.is_finite() && light.intensity.is_finite())
+            && colors
+                .flat_map(|color| color.as_rgba_f32())
+                .all(f32::is_finite);
+        if !is_finite {
+            return invalid(String::from("not all numbers are finite"));
+        }
+        Ok(())
+    }
+}
+
+impl HypercubeData {
+    /// An `n` dimensional hypercube of the default style, turning in the q2q3 and q1q4 planes,
+    /// or q1q3 for a 3-cube
+    pub fn new(n: usize) -> Self {
+        let rotations = HypercubeRotations::new(n, &[((1, 2), 1.0), ((0, 3.min(n - 1)), 0.5)]);
+        let style = HypercubeStyle::default();
+        Self {
+            dimension: n,
+            rotations: usize::pair_permutations(0, n - 1)
+                .into_iter()
+                .map(|plane| (plane.0, plane.1, rotations[&plane].0, rotations[&plane].1))
+                .collect(),
+            paused: false,
+            size: default_size(),
+            vertices: None,
+            edge_thickness: style.edge_thickness,
+            edge_color: style.edge_color,
+            face_color: style.face_color,
+            unlit: style.unlit,
+            face_color_by_plane: style.face_color_by_plane,
+            isolate_face_planes: style.isolate_face_planes,
+            selected_face_planes: Vec::new(),
+            face_blend_mode: style.face_blend_mode,
+        }
+    }
+
+    /// Checks that the hypercube can be shown, describing what is wrong otherwise
+    fn validate(&self) -> Result<(), String> {
+        let n = self.dimension;
+        if !(MIN_DIMENSION..=MAX_DIMENSION).contains(&n) {
+            return Err(format!(
+                "dimension {n} is not between {MIN_DIMENSION} and {MAX_DIMENSION}"
+            ));
+        }
+
+        let plane_name = |d1: usize, d2: usize| format!("q{}q{}", d1 + 1, d2 + 1);
+        let check_plane = |d1: usize, d2: usize| {
+            if d1 >= n || d2 >= n {
+                return Err(format!(
+                    "plane {} is not in the {n}-cube",
+                    plane_name(d1, d2)
+                ));
+            }
+            if d1 == d2 {
+                return Err(format!("plane {} is not a plane", plane_name(d1, d2)));
+            }
+            if d1 > d2 {
+                return Err(format!(
+                    "plane {} must be written {}",
+                    plane_name(d1, d2),
+                    plane_name(d2, d1)
+                ));
+            }
+            Ok(())
+        };
+        let mut planes = HashSet::new();
+        for (d1, d2, angle, vel) in &self.rotations {
+            check_plane(*d1, *d2)?;
+            if !planes.insert((*d1, *d2)) {
+                return Err(format!("plane {} is repeated", plane_name(*d1, *d2)));
+            }
+            if !angle.is_finite() || !vel.is_finite() {
+                return Err(format!(
+                    "rotation of plane {} is not a finite number",
+                    plane_name(*d1, *d2)
+                ));
+            }
+        }
+        for (d1, d2) in &self.selected_face_planes {
+            check_plane(*d1, *d2)?;
+        }
+
+        if !(self.size.is_finite() && self.size > 0.0) {
+            return Err(format!("size {} is not a positive number", self.size));
+        }
+        if let Some(vertices) = &self.vertices {
+            if vertices.len() != 1 << n || vertices.iter().any(|vertex| vertex.len() != n) {
+                return Err(format!("vertices don't match the {n}-cube"));
+            }
+            if !vertices.iter().flatten().all(|x| x.is_finite()) {
+                return Err(String::from("vertices are not finite numbers"));
+            }
+        }
+
+        let is_finite = self.edge_thickness.is_finite()
+            && [self.edge_color, self.face_color]
+                .iter()
+                .flat_map(|color| color.as_rgba_f32())
+                .all(f32::is_finite);
+        if !is_finite {
+            return Err(String::from("not all numbers are finite"));
+        }
+        Ok(())
+    }
+
+    /// Captures the hypercube in its current orientation
+    pub fn snapshot(hypercube: &HypercubeComponentsReadOnlyItem) -> Self {
+        let style = hypercube.style;
+        let mut selected_face_planes = Vec::from_iter(style.selected_face_planes.iter().copied());
+        selected_face_planes.sort();
+        Self {
+            dimension: **hypercube.dimension,
+            rotations: hypercube
+                .hypercube
+                .planes_of_rotation
+                .iter()
+                .filter_map(|plane| {
+                    let (angle, vel) = hypercube.rotations.get(plane)?;
+                    Some((plane.0, plane.1, *angle, *vel))
+                })
+                .collect(),
+            paused: **hypercube.paused,
+            size: hypercube.hypercube.ncube.size,
+            vertices: Some(hypercube.hypercube.ncube.vertices.0.clone()),
+            edge_thickness: style.edge_thickness,
+            edge_color: style.edge_color,
+            face_color: style.face_color,
+            unlit: style.unlit,
+            face_color_by_plane: style.face_color_by_plane,
+            isolate_face_planes: style.isolate_face_planes,
+            selected_face_planes,
+            face_blend_mode: style.face_blend_mode,
+        }
+    }
+
+    /// Hypercube described by the data, paused if either of them is
+    pub fn bundle(&self, paused: bool) -> HypercubeBundle {
+        let n = self.dimension;
+        let mut ncube = ncube_core::NCube::new(n, self.size);
+        let mut rotations = HashMap::new();
+        let mut planes_of_rotation = Vec::new();
+        let mut angles = Vec::new();
+        for (d1, d2, angle, vel) in &self.rotations {
+            rotations.insert((*d1, *d2), (*angle, *vel));
+            planes_of_rotation.push((*d1, *d2));
+            angles.push(*angle);
+        }
+        match &self.vertices {
+            Some(vertices) => ncube.vertices.0 = vertices.clone(),
+            None => {
+                ncube.rotate(&planes_of_rotation, &angles);
+            }
+        }
+        for plane in usize::pair_permutations(0, n - 1) {
+            if let Entry::Vacant(entry) = rotations.entry(plane) {
+                entry.insert((0.0, 0.0));
+                planes_of_rotation.push(plane);
+            }
+        }
+        HypercubeBundle {
+            hypercube: Hypercube {
+                vertices_3d: ncube.vertices_3d(),
+                ncube,
+                planes_of_rotation,
+            },
+            dimension: HypercubeDimension(n),
+            rotations: HypercubeRotations(rotations),
+            paused: HypercubePaused(self.paused || paused),
+            style: self.style(),
+            highlight: HypercubeHighlight::default(),
+            unfolding: HypercubeUnfolding::default(),
+            extrusion: HypercubeExtrusion::default(),
+            walk: HypercubeWalk::default(),
+            spatial: SpatialBundle::default(),
+        }
+    }
+
+    fn style(&self) -> HypercubeStyle {
+        HypercubeStyle {
+            edge_thickness: self.edge_thickness,
+            edge_color: self.edge_color,
+            face_color: self.face_color,
+            unlit: self.unlit,
+            face_color_by_plane: self.face_color_by_plane,
+            isolate_face_planes: self.isolate_face_planes,
+            selected_face_planes: self.selected_face_planes.iter().copied().collect(),
+            face_blend_mode: self.face_blend_mode,
+        }
+    }
+
+    /// Turns `hypercube` into the one described by the data, leaving its pause state as is
+    pub fn apply(&self, hypercube: &mut HypercubeComponentsItem) {
+        let bundle = self.bundle(false);
+        *hypercube.hypercube = bundle.hypercube;
+        hypercube.dimension.set_if_neq(bundle.dimension);
+        hypercube.rotations.set_if_neq(bundle.rotations);
+        hypercube.style.set_if_neq(bundle.style);
+    }
+
+    /// Applies the dimension, angular velocities and style of the data to `hypercube`, keeping
+    /// its orientation
+    pub fn apply_settings(&self, hypercube: &mut HypercubeComponentsItem) {
+        hypercube
+            .dimension
+            .set_if_neq(HypercubeDimension(self.dimension));
+        let mut rotations = hypercube.rotations.clone();
+        for (d1, d2, _, vel) in &self.rotations {
+            let angle = rotations.get(&(*d1, *d2)).map_or(0.0, |v| v.0);
+            rotations.insert((*d1, *d2), (angle, *vel));
+        }
+        hypercube.rotations.set_if_neq(rotations);
+        hypercube.style.set_if_neq(self.style());
+    }
+}
+
+/// Everything a data file restores
+#[derive(SystemParam)]
+pub struct NCubeState<'w, 's> {
+    commands: Commands<'w, 's>,
+    hypercube_order: ResMut<'w, HypercubeOrder>,
+    selected_hypercube: ResMut<'w, SelectedHypercube>,
+    q_hypercubes: Query<'w, 's, HypercubeComponents>,
+    q_hypercube_entities: Query<'w, 's, Entity, With<Hypercube>>,
+    scene_background: ResMut<'w, SceneBackground>,
+    scene_lights: ResMut<'w, SceneLights>,
+    scene_ambient_light: ResMut<'w, SceneAmbientLight>,
+    scene_show_grid: ResMut<'w, SceneShowGrid>,
+    scene_show_axes: ResMut<'w, SceneShowAxes>,
+    show_info_text: ResMut<'w, ShowInfoText>,
+    q_camera: Query<'w, 's, (&'static mut Transform, &'static mut Projection), With<MainCamera>>,
+    q_window: Query<'w, 's, &'static mut Window, With<PrimaryWindow>>,
+}
+
+impl NCubeState<'_, '_> {
+    /// Hypercube that the settings work on
+    pub fn selected(&self) -> Option<Entity> {
+        **self.selected_hypercube
+    }
+
+    /// Captures the current scene
+    pub fn snapshot(&self) -> NCubeData {
+        let (camera_transform, camera_projection) = self.q_camera.single();
+        let hypercubes: Vec<_> = self
+            .hypercube_order
+            .iter()
+            .filter_map(|entity| Some((*entity, self.q_hypercubes.get(*entity).ok()?)))
+            .collect();
+        NCubeData {
+            version: DATA_FILE_VERSION,
+            metadata: None,
+            selected: hypercubes
+                .iter()
+                .position(|(entity, _)| Some(*entity) == **self.selected_hypercube)
+                .unwrap_or_default(),
+            hypercubes: hypercubes
+                .iter()
+                .map(|(_, hypercube)| HypercubeData::snapshot(hypercube))
+                .collect(),
+            camera_transform: CameraTransform {
+                translation: camera_transform.translation,
+                rotation: camera_transform.rotation,
+                scale: camera_transform.scale,
+            },
+            orthographic_projection: matches!(camera_projection, Projection::Orthographic(_)),
+            background: **self.scene_background,
+            lights: self.scene_lights.to_vec(),
+            ambient_light: **self.scene_ambient_light,
+            show_grid: **self.scene_show_grid,
+            show_axes: **self.scene_show_axes,
+            fullscreen: self
+                .q_window
+                .get_single()
+                .is_ok_and(|window| window.mode != WindowMode::Windowed),
+            show_info_text: **self.show_info_text,
+        }
+    }
+
+    /// Replaces the scene with the one described by `data`, its hypercubes respawned in its
+    /// order. They are all paused if `paused` is.
+    pub fn apply(&mut self, data: NCubeData, paused: bool) {
+        let (mut camera_transform, mut camera_projection) = self.q_camera.single_mut();
+        *camera_transform = Transform {
+            translation: data.camera_transform.translation,
+            scale: data.camera_transform.scale,
+            rotation: data.camera_transform.rotation,
+        };
+        *camera_projection = get_default_camera_projection(
+            data.orthographic_projection
+                .then(|| camera_transform.translation.length()),
+        );
+        if let Ok(mut window) = self.q_window.get_single_mut() {
+            window.mode = if data.fullscreen {
+                WindowMode::BorderlessFullscreen
+            } else {
+                WindowMode::Windowed
+            };
+        }
+        self.apply_appearance(&data);
+        for entity in &self.q_hypercube_entities {
+            self.commands.entity(entity).despawn_recursive();
+        }
+        let entities: Vec<Entity> = data
+            .hypercubes
+            .iter()
+            .map(|hypercube| {
+                let bundle = hypercube.bundle(paused);
+                self.commands.spawn((bundle, ArrangedHypercube)).id()
+            })
+            .collect();
+        **self.selected_hypercube = entities.get(data.selected).copied();
+        **self.hypercube_order = entities;
+    }
+
+    /// Whether a hypercube is yet to be rebuilt for a new dimension
+    pub fn is_rebuilding(&self) -> bool {
+        self.q_hypercubes
+            .iter()
+            .any(|hypercube| hypercube.hypercube.ncube.dimensions != **hypercube.dimension)
+    }
+
+    /// Applies the settings of the scene and of the selected hypercube of `data` to the selected
+    /// hypercube, keeping its orientation and the position of the camera
+    pub fn apply_settings(&mut self, data: &NCubeData) {
+        let (camera_transform, mut camera_projection) = self.q_camera.single_mut();
+        if matches!(*camera_projection, Projection::Orthographic(_)) != data.orthographic_projection
+        {
+            *camera_projection = get_default_camera_projection(
+                data.orthographic_projection
+                    .then(|| camera_transform.translation.length()),
+            );
+        }
+        self.apply_appearance(data);
+        let selected = self
+            .selected_hypercube
+            .and_then(|entity| self.q_hypercubes.get_mut(entity).ok());
+        if let (Some(mut hypercube), Some(hypercube_data)) =
+            (selected, data.hypercubes.get(data.selected))
+        {
+            hypercube_data.apply_settings(&mut hypercube);
+        }
+    }
+
+    fn apply_appearance(&mut self, data: &NCubeData) {
+        **self.show_info_text = data.show_info_text;
+        **self.scene_background = data.background;
+        **self.scene_lights = data.lights.clone();
+        **self.scene_ambient_light = data.ambient_light;
+        **self.scene_show_grid = data.show_grid;
+        **self.scene_show_axes = data.show_axes;
+    }
+}
+
+fn apply_pending_ncube_data(
+    mut pending_ncube_data: ResMut<PendingNCubeData>,
+    load_paused: Res<LoadPaused>,
+    mut ncube_state: NCubeState,
+) {
+    if let Some(data) = pending_ncube_data.take() {
+        ncube_state.apply(data, **load_paused);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn round_trip_bundled_data_files() {
+        for data in crate::gallery::PRESETS {
+            let data = NCubeData::parse(data).unwrap();
+            assert_eq!(data.version, DATA_FILE_VERSION);
+            let exported = serde_json::to_string_pretty(&data).unwrap();
+            assert_eq!(NCubeData::parse(&exported).unwrap(), data);
+        }
+    }
+    #[test]
+    fn default_is_valid() {
+        let data = NCubeData::default();
+        assert_eq!(data.validate(), Ok(()));
+        let hypercube = &data.hypercubes[data.selected];
+        assert_eq!(
+            hypercube.rotations.len(),
+            hypercube.dimension * (hypercube.dimension - 1) / 2
+        );
+    }
+    #[test]
+    fn share_string_round_trip() {
+        let mut data = NCubeData::parse(include_str!("../data/demo.data")).unwrap();
+        let share_string = data.to_share_string();
+        data.hypercubes
+            .iter_mut()
+            .for_each(|hypercube| hypercube.vertices = None);
+        assert_eq!(NCubeData::from_share_string(&share_string).unwrap(), data);
+        assert!(NCubeData::from_share_string("not a share string").is_err());
+    }
+    #[test]
+    fn migrate_unversioned() {
+        let data = NCubeData::parse(r#"{ "dimension": 4, "rotations": [[0, 1, 7.0, 0.5]] }"#);
+        let data = data.unwrap();
+        assert_eq!(data.version, DATA_FILE_VERSION);
+        assert_eq!(data.hypercubes.len(), 1);
+        assert_eq!(
+            data.hypercubes[0].rotations,
+            vec![(0, 1, 7.0 % std::f64::consts::TAU, 0.5)]
+        );
+        assert!(data.hypercubes[0].paused);
+        assert!(matches!(
+            NCubeData::parse(r#"{ "version": 99, "dimension": 4, "rotations": [] }"#),
+            Err(DataFileError::Invalid(_))
+        ));
+    }
+    #[test]
+    fn published_schema_is_up_to_date() {
+        assert_eq!(
+            NCubeData::schema(),
+            include_str!("../schema/ncube-data.schema.json").trim_end()
+        );
+    }
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            NCubeData::parse(&format!(
+                "{{\n  \"version\": {DATA_FILE_VERSION},\n  \"hypercubes\": [{{ \"dimension\": 4, \"rotations\": [[0, 1, 0.0]] }}]\n}}"
+            ))
+            .err(),
+            Some(DataFileError::Parse {
+                message: String::from("invalid length 3, expected a tuple of size 4"),
+                line: 3,
+                column: 60,
+            })
+        );
+        assert_eq!(
+            NCubeData::parse(
+                "{\n  \"version\": 2,\n  \"dimension\": 4,\n  \"rotations\": [[0, 1, 0.0]]\n}"
+            )
+            .err(),
+            Some(DataFileError::Parse {
+                message: String::from("invalid length 3, expected a tuple of size 4"),
+                line: 4,
+                column: 27,
+            })
+        );
+        // Unversioned files keep the position of their errors through the migrations
+        assert_eq!(
+            NCubeData::parse("{\n  \"dimension\": 4,\n  \"paused\": 1\n}").err(),
+            Some(DataFileError::Parse {
+                message: String::from("invalid type: integer `1`, expected a boolean"),
+                line: 3,
+                column: 13,
+            })
+        );
+        let invalid = |data: &str| matches!(NCubeData::parse(data), Err(DataFileError::Invalid(_)));
+        assert!(invalid(r#"{ "dimension": 12, "rotations": [] }"#));
+        assert!(invalid(
+            r#"{ "dimension": 4, "rotations": [[0, 4, 0.0, 0.0]] }"#
+        ));
+        assert!(invalid(
+            r#"{ "dimension": 4, "rotations": [[1, 1, 0.0, 0.0]] }"#
+        ));
+        assert!(invalid(
+            r#"{ "dimension": 4, "rotations": [[1, 0, 0.0, 0.0]] }"#
+        ));
+        assert!(invalid(
+            r#"{ "dimension": 4, "rotations": [[0, 1, 0.0, 0.0], [0, 1, 0.0, 1.0]] }"#
+        ));
+        assert!(invalid(
+            r#"{ "dimension": 4, "rotations": [], "selected_face_planes": [[3, 2]] }"#
+        ));
+        assert!(invalid(
+            r#"{ "dimension": 4, "rotations": [], "selected_face_planes": [[2, 5]] }"#
+        ));
+        assert!(invalid(
+            r#"{ "dimension": 3, "rotations": [], "size": 0.0 }"#
+        ));
+        assert!(invalid(
+            r#"{ "dimension": 3, "rotations": [], "vertices": [[0.5, 0.5, 0.5]] }"#
+        ));
+        assert!(invalid(r#"{ "version": 3, "hypercubes": [] }"#));
+        assert!(invalid(
+            r#"{ "version": 3, "hypercubes": [{ "dimension": 3, "rotations": [] }], "selected": 1 }"#
+        ));
+        assert_eq!(
+            NCubeData::parse(
+                r#"{ "version": 3, "hypercubes": [
+                    { "dimension": 3, "rotations": [] },
+                    { "dimension": 4, "rotations": [[0, 4, 0.0, 0.0]] }
+                ] }"#
+            )
+            .err(),
+            Some(DataFileError::Invalid(String::from(
+                "hypercube 2: plane q1q5 is not in the 4-cube"
+            )))
+        );
+    }
+}
@@ -0,0 +1,37 @@
+//! Presets bundled with the app, listed in the gallery
+
+use crate::data::NCubeData;
+
+/// Contents of the bundled data files, in gallery order
+pub const PRESETS: [&str; 7] = [
+    include_str!("../data/cube.data"),
+    include_str!("../data/tesseract-q1q4.data"),
+    include_str!("../data/tesseract-isoclinic.data"),
+    include_str!("../data/penteract-q4q5.data"),
+    include_str!("../data/penteract-q1q4-q3q5.data"),
+    include_str!("../data/hexeract-q1q4-q2q5-q3q6.data"),
+    include_str!("../data/demo.data"),
+];
+
+pub fn presets() -> Vec<NCubeData> {
+    PRESETS
+        .iter()
+        .filter_map(|preset| NCubeData::parse(preset).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn presets_have_metadata() {
+        let presets = presets();
+        assert_eq!(presets.len(), PRESETS.len());
+        for preset in presets {
+            let metadata = preset.metadata.unwrap();
+            assert!(!metadata.title.is_empty());
+            assert!(!metadata.description.is_empty());
+            assert!(!metadata.author.is_empty());
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use crate::data::{CameraTransform, NCubeData, NCubeState};
+use crate::hypercube::Hypercube;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::EguiContexts;
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Histories>()
+            .add_systems(Update, update_history);
+    }
+}
+
+const MAX_HISTORY: usize = 100;
+
+/// Undo history of the scene, shared by all the hypercubes, and of each hypercube, so that
+/// undoing never touches another one
+#[derive(Resource, Default)]
+struct Histories {
+    scene: History,
+    hypercubes: HashMap<Entity, History>,
+    /// Number of steps recorded so far, to undo the latest of the scene's and the hypercube's
+    steps: u64,
+}
+
+impl Histories {
+    /// Records the settings of the scene, and those of the `selected` hypercube
+    fn record(&mut self, selected: Entity, data: NCubeData, is_dragging: bool) {
+        let step = self.steps + 1;
+        let hypercube = self.hypercubes.entry(selected).or_default();
+        let is_scene_changed = self.scene.record(scene_settings(&data), is_dragging, step);
+        let is_hypercube_changed = hypercube.record(settings(data), is_dragging, step);
+        if is_scene_changed || is_hypercube_changed {
+            self.steps = step;
+        }
+        // Nothing undone before a new step can be redone after it
+        if is_scene_changed {
+            hypercube.redo.clear();
+        }
+        if is_hypercube_changed {
+            self.scene.redo.clear();
+        }
+    }
+
+    /// Undoes or redoes the latest step of the scene or of the `selected` hypercube, and returns
+    /// the settings to apply
+    fn undo(&mut self, selected: Entity, is_redo: bool) -> Option<NCubeData> {
+        let Self {
+            scene, hypercubes, ..
+        } = self;
+        let hypercube = hypercubes.entry(selected).or_default();
+        let is_scene = if is_redo {
+            // Redone the other way around from how they were undone
+            match (scene.next_redo(), hypercube.next_redo()) {
+                (Some(scene_step), Some(hypercube_step)) => scene_step < hypercube_step,
+                (scene_step, _) => scene_step.is_some(),
+            }
+        } else {
+            scene.next_undo() > hypercube.next_undo()
+        };
+        let history = if is_scene {
+            &mut *scene
+        } else {
+            &mut *hypercube
+        };
+        if is_redo {
+            history.redo()?;
+        } else {
+            history.undo()?;
+        }
+        let (scene, hypercube) = (scene.current.as_ref()?, hypercube.current.as_ref()?);
+        Some(NCubeData {
+            hypercubes: hypercube.hypercubes.clone(),
+            ..scene.clone()
+        })
+    }
+}
+
+/// Settings before and after each change, for undo and redo
+#[derive(Default)]
+struct History {
+    /// Settings before each step, along with the step
+    undo: Vec<(u64, NCubeData)>,
+    /// Settings after each step undone, along with the step
+    redo: Vec<(u64, NCubeData)>,
+    current: Option<NCubeData>,
+    /// Whether changes are merged into the last step, while a slider is dragged
+    is_merging: bool,
+}
+
+impl History {
+    /// Records the settings, and returns whether they make up a new `step`
+    fn record(&mut self, settings: NCubeData, is_dragging: bool, step: u64) -> bool {
+        let Some(current) = &self.current else {
+            self.current = Some(settings);
+            return false;
+        };
+        if *current == settings {
+            self.is_merging &= is_dragging;
+            return false;
+        }
+        let is_new_step = !self.is_merging;
+        if is_new_step {
+            self.undo
+                .extend(self.current.take().map(|current| (step, current)));
+            if self.undo.len() > MAX_HISTORY {
+                self.undo.remove(0);
+            }
+            self.redo.clear();
+        }
+        self.is_merging = is_dragging;
+        self.current = Some(settings);
+        is_new_step
+    }
+
+    fn undo(&mut self) -> Option<&NCubeData> {
+        let (step, previous) = self.undo.pop()?;
+        self.redo.extend(
+            self.current
+                .replace(previous)
+                .map(|current| (step, current)),
+        );
+        self.is_merging = false;
+        self.current.as_ref()
+    }
+
+    fn redo(&mut self) -> Option<&NCubeData> {
+        let (step, next) = self.redo.pop()?;
+        self.undo
+            .extend(self.current.replace(next).map(|current| (step, current)));
+        self.is_merging = false;
+        self.current.as_ref()
+    }
+
+    /// Step that would be undone next
+    fn next_undo(&self) -> Option<u64> {
+        self.undo.last().map(|(step, _)| *step)
+    }
+
+    /// Step that would be redone next
+    fn next_redo(&self) -> Option<u64> {
+        self.redo.last().map(|(step, _)| *step)
+    }
+}
+
+/// Settings of the scene alone, the same whichever hypercube is selected
+fn scene_settings(data: &NCubeData) -> NCubeData {
+    NCubeData {
+        hypercubes: Vec::new(),
+        selected: 0,
+        camera_transform: CameraTransform::default(),
+        fullscreen: false,
+        ..data.clone()
+    }
+}
+
+/// Leaves out the other hypercubes, the settings of the scene, and what changes without the
+/// settings being touched
+fn settings(mut data: NCubeData) -> NCubeData {
+    data.hypercubes = data
+        .hypercubes
+        .into_iter()
+        .skip(data.selected)
+        .take(1)
+        .collect();
+    for hypercube in &mut data.hypercubes {
+        hypercube
+            .rotations
+            .iter_mut()
+            .for_each(|rotation| rotation.2 = 0.0);
+        hypercube
+            .rotations
+            .sort_by_key(|rotation| (rotation.0, rotation.1));
+        hypercube.vertices = None;
+        hypercube.paused = false;
+    }
+    NCubeData {
+        hypercubes: data.hypercubes,
+        ..default()
+    }
+}
+
+fn update_history(
+    mut contexts: EguiContexts,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut histories: ResMut<Histories>,
+    mut removed_hypercubes: RemovedComponents<Hypercube>,
+    mut ncube_state: NCubeState,
+) {
+    for entity in removed_hypercubes.read() {
+        histories.hypercubes.remove(&entity);
+    }
+    let Some(selected) = ncube_state.selected() else {
+        return;
+    };
+    let context = contexts.ctx_mut();
+    let is_modifier_pressed = keyboard_input.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+    if is_modifier_pressed
+        && keyboard_input.just_pressed(KeyCode::KeyZ)
+        && !context.wants_keyboard_input()
+    {
+        let is_redo = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        if let Some(settings) = histories.undo(selected, is_redo) {
+            ncube_state.apply_settings(&settings);
+        }
+        return;
+    }
+    if ncube_state.is_rebuilding() {
+        return;
+    }
+    let is_dragging = context.is_using_pointer();
+    histories.record(selected, ncube_state.snapshot(), is_dragging);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn data(dimension: usize) -> NCubeData {
+        let mut data = NCubeData::default();
+        data.hypercubes[0].dimension = dimension;
+        data
+    }
+    fn dimension(data: &NCubeData) -> usize {
+        data.hypercubes[0].dimension
+    }
+    #[test]
+    fn undo_redo() {
+        let mut history = History::default();
+        history.record(data(3), false, 1);
+        history.record(data(4), false, 2);
+        history.record(data(5), false, 3);
+        assert_eq!(history.undo().map(dimension), Some(4));
+        assert_eq!(history.undo().map(dimension), Some(3));
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().map(dimension), Some(4));
+        history.record(data(6), false, 4);
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo().map(dimension), Some(4));
+    }
+    #[test]
+    fn merge_drags() {
+        let mut history = History::default();
+        history.record(data(3), false, 1);
+        history.record(data(4), true, 2);
+        history.record(data(5), true, 3);
+        history.record(data(5), false, 4);
+        history.record(data(6), true, 5);
+        assert_eq!(history.undo().map(dimension), Some(5));
+        assert_eq!(history.undo().map(dimension), Some(3));
+    }
+    #[test]
+    fn settings_of_selected_hypercube() {
+        let mut scene = data(3);
+        scene.hypercubes.extend(data(4).hypercubes);
+        scene.selected = 1;
+        let settings = settings(scene);
+        assert_eq!(settings.hypercubes.len(), 1);
+        assert_eq!(settings.selected, 0);
+        assert_eq!(dimension(&settings), 4);
+    }
+    #[test]
+    fn scene_settings_across_selection() {
+        let (first, second) = (Entity::from_raw(0), Entity::from_raw(1));
+        let mut scene = data(3);
+        scene.hypercubes.extend(data(4).hypercubes);
+        let mut histories = Histories::default();
+        histories.record(first, scene.clone(), false);
+        scene.show_grid = true;
+        histories.record(first, scene.clone(), false);
+        scene.selected = 1;
+        histories.record(second, scene.clone(), false);
+        scene.hypercubes[1].dimension = 5;
+        histories.record(second, scene.clone(), false);
+
+        let undone = histories.undo(second, false).unwrap();
+        assert_eq!(dimension(&undone), 4);
+        assert!(undone.show_grid);
+        // The grid was shown with the first hypercube selected, and is hidden again all the same
+        let undone = histories.undo(second, false).unwrap();
+        assert_eq!(dimension(&undone), 4);
+        assert!(!undone.show_grid);
+        assert!(histories.undo(second, false).is_none());
+        assert!(histories.undo(first, false).is_none());
+
+        let redone = histories.undo(second, true).unwrap();
+        assert!(redone.show_grid);
+        assert_eq!(dimension(&redone), 4);
+        assert_eq!(dimension(&histories.undo(second, true).unwrap()), 5);
+    }
+}
@@ -0,0 +1,1492 @@
+//! Hypercubes as entities, rendered from their components
+
+use crate::edge;
+use crate::impl_default;
+use crate::oit::WeightedBlendedPlugin;
+use crate::resources::{FaceBlendMode, SIZE};
+use crate::vec::{PolygonNormal, Vertices3D};
+use bevy::ecs::query::QueryData;
+use bevy::pbr::AlphaMode;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::view::RenderLayers;
+use bevy::transform::TransformSystem;
+use ncube_core::{walk, ExtendedMathOps, NCell, NNet, NSection};
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::FRAC_PI_2;
+
+/// Rotates the entities spawned with a [`HypercubeBundle`] and keeps their edges and faces,
+/// spawned as children, up to date
+pub struct NCubePlugin;
+
+impl Plugin for NCubePlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            PostUpdate,
+            NCubeSystems.before(TransformSystem::TransformPropagate),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                rotate_hypercubes,
+                spawn_hypercube_meshes,
+                cut_hypercubes,
+                propagate_render_layers,
+                update_hypercube_meshes,
+                walk_hypercubes,
+                mark_highlighted_vertices,
+            )
+                .chain()
+                .in_set(NCubeSystems),
+        )
+        .add_plugins(WeightedBlendedPlugin);
+    }
+}
+
+/// Systems that rotate the hypercubes and update their meshes
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NCubeSystems;
+
+/// Geometry of a hypercube in its current orientation
+#[derive(Component, Clone)]
+pub struct Hypercube {
+    pub ncube: ncube_core::NCube,
+    /// Planes of rotation, in the order the rotations are applied
+    pub planes_of_rotation: Vec<(usize, usize)>,
+    /// Vertices projected into the 3D scene, those of the net while the hypercube is unfolded
+    pub vertices_3d: Vec<Vec3>,
+}
+
+impl Hypercube {
+    pub fn new(dimension: usize, size: f64) -> Self {
+        let ncube = ncube_core::NCube::new(dimension, size);
+        Self {
+            planes_of_rotation: usize::pair_permutations(0, dimension - 1),
+            vertices_3d: ncube.vertices_3d(),
+            ncube,
+        }
+    }
+}
+
+#[derive(Component, Deref, DerefMut, Clone, Copy, PartialEq)]
+pub struct HypercubeDimension(pub usize);
+
+/// k: Plane
+/// v: Angle, angular velocity
+#[derive(Component, Deref, DerefMut, Clone, PartialEq, Default)]
+pub struct HypercubeRotations(pub HashMap<(usize, usize), (f64, f64)>);
+
+impl HypercubeRotations {
+    /// Every plane of an `n` dimensional hypercube at rest, except for the given `velocities`
+    pub fn new(n: usize, velocities: &[((usize, usize), f64)]) -> Self {
+        let mut rotations = HashMap::from_iter(
+            usize::pair_permutations(0, n - 1)
+                .into_iter()
+                .map(|plane| (plane, (0.0, 0.0))),
+        );
+        for (plane, vel) in velocities {
+            rotations.insert(*plane, (0.0, *vel));
+        }
+        Self(rotations)
+    }
+}
+
+#[derive(Component, Deref, DerefMut, Clone, Copy, PartialEq, Default)]
+pub struct HypercubePaused(pub bool);
+
+/// Appearance of the edges and faces of a hypercube
+#[derive(Component, Clone, PartialEq)]
+pub struct HypercubeStyle {
+    pub edge_thickness: f32,
+    pub edge_color: Color,
+    pub face_color: Color,
+    pub unlit: bool,
+    /// Whether each face gets the color of the plane it is parallel to
+    pub face_color_by_plane: bool,
+    /// Whether only the faces parallel to `selected_face_planes` are shown
+    pub isolate_face_planes: bool,
+    pub selected_face_planes: HashSet<(usize, usize)>,
+    pub face_blend_mode: FaceBlendMode,
+}
+impl_default!(HypercubeStyle => Self {
+    edge_thickness: 0.01 * SIZE,
+    edge_color: Color::CYAN,
+    face_color: Color::CYAN.with_a(0.1),
+    unlit: false,
+    face_color_by_plane: false,
+    isolate_face_planes: false,
+    selected_face_planes: HashSet::new(),
+    face_blend_mode: FaceBlendMode::default(),
+});
+
+/// Cell of a hypercube that stands out from the rest
+#[derive(Component, Clone, PartialEq)]
+pub struct HypercubeHighlight {
+    pub cell: Option<NCell>,
+    pub color: Color,
+    /// Whether everything outside of the cell is faded out
+    pub fade_unhighlighted: bool,
+}
+impl_default!(HypercubeHighlight => Self {
+    cell: None,
+    color: Color::ORANGE,
+    fade_unhighlighted: false,
+});
+
+/// Opening of a hypercube into its net, which takes over from its rotations while `enabled`
+#[derive(Component, Clone, PartialEq, Default)]
+pub struct HypercubeUnfolding {
+    pub enabled: bool,
+    /// Angle each facet is turned by about its hinge, from 0 when folded to 90° when flat
+    pub angle: f64,
+    /// Angular velocity the facets are turned at until they are folded or flat
+    pub velocity: f64,
+    /// Whether the net of a 5-cube and up is unfolded further, facet by facet, down to 3D. Its
+    /// cells add up quickly: 80 cubes for a 5-cube, and 960 for a 6-cube.
+    pub recursive: bool,
+}
+
+/// Animation of the changes of dimension by one: the hypercube is swept out of the one a
+/// dimension lower along its last axis, or flattened back into it before being rebuilt
+#[derive(Component, Clone, PartialEq)]
+pub struct HypercubeExtrusion {
+    pub enabled: bool,
+    /// How far the last axis is swept out, from 0 when flat to 1 when done
+    pub progress: f64,
+}
+impl_default!(HypercubeExtrusion => Self {
+    enabled: false,
+    progress: 1.0,
+});
+
+/// Sweeps of the last axis per second
+const EXTRUSION_SPEED: f64 = 0.75;
+
+/// Path over the vertex graph of a hypercube
+#[derive(Clone, PartialEq, Default)]
+pub enum WalkPath {
+    /// Hamiltonian cycle in the order of the reflected binary Gray code
+    #[default]
+    GrayCode,
+    /// Shortest path from a vertex that flips the bits of `axes` in turn
+    Shortest { from: usize, axes: Vec<usize> },
+}
+
+impl WalkPath {
+    /// Vertices visited in turn, back to the first at the end of the Gray code cycle
+    pub fn vertices(&self, n: usize) -> Vec<usize> {
+        match self {
+            Self::GrayCode => {
+                let mut cycle = walk::gray_code(n);
+                cycle.push(cycle[0]);
+                cycle
+            }
+            Self::Shortest { from, axes } => walk::shortest_path(*from, axes),
+        }
+    }
+
+    /// Whether the path only goes through vertices of an `n` dimensional hypercube
+    fn fits(&self, n: usize) -> bool {
+        match self {
+            Self::GrayCode => true,
+            Self::Shortest { from, axes } => *from < 1 << n && axes.iter().all(|d| *d < n),
+        }
+    }
+}
+
+/// Marker walking over the edges of a hypercube, which stand out once traversed
+#[derive(Component, Clone, PartialEq)]
+pub struct HypercubeWalk {
+    pub enabled: bool,
+    pub path: WalkPath,
+    /// Edges walked since the start of the path, the fraction of the current one included
+    pub position: f64,
+    /// Edges walked per second
+    pub speed: f64,
+    pub color: Color,
+}
+impl_default!(HypercubeWalk => Self {
+    enabled: false,
+    path: WalkPath::default(),
+    position: 0.0,
+    speed: 2.0,
+    color: Color::CYAN,
+});
+
+impl HypercubeWalk {
+    /// Vertex the marker last went through on an `n` dimensional hypercube
+    pub fn vertex(&self, n: usize) -> usize {
+        let vertices = self.path.vertices(n);
+        vertices[(self.position as usize).min(vertices.len() - 1)]
+    }
+}
+
+/// Edges of the hypercube traversed by its walk so far, each from its lower vertex
+#[derive(Component, Default, PartialEq)]
+struct WalkedEdges(HashSet<(usize, usize)>);
+
+impl WalkedEdges {
+    fn contains(&self, (i, j): (usize, usize)) -> bool {
+        self.0.contains(&(i.min(j), i.max(j)))
+    }
+}
+
+#[derive(Component)]
+pub struct WalkMarker;
+
+#[derive(Component)]
+pub struct VertexMarker;
+
+/// Net that the edges and faces of an unfolded hypercube are spawned from
+#[derive(Component)]
+pub struct HypercubeNet(pub NNet);
+
+impl HypercubeNet {
+    /// Vertex of the hypercube that vertex `i` of [`Hypercube::vertices_3d`] comes from
+    pub fn origin(net: Option<&Self>, i: usize) -> usize {
+        net.map_or(i, |net| net.0.origins[i])
+    }
+}
+
+/// How a hypercube is brought down into the 3D scene, in perspective when it has none
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum HypercubeProjection {
+    #[default]
+    Perspective,
+    /// Each dimension past the third dropped, so that parallel edges stay parallel
+    Orthographic,
+    /// Only the [`NSection`] through its center is shown, its edges and faces hidden
+    CrossSection,
+}
+
+/// Edge of the cross-section of a hypercube, spawned as a child of it
+#[derive(Component)]
+pub struct SectionEdge;
+
+/// Faces of the cross-section of a hypercube, in a single mesh spawned as a child of it
+#[derive(Component)]
+pub struct SectionFaces;
+
+/// Everything needed to spawn a rotating hypercube. Its `transform` places it in the scene.
+#[derive(Bundle)]
+pub struct HypercubeBundle {
+    pub hypercube: Hypercube,
+    pub dimension: HypercubeDimension,
+    pub rotations: HypercubeRotations,
+    pub paused: HypercubePaused,
+    pub style: HypercubeStyle,
+    pub highlight: HypercubeHighlight,
+    pub unfolding: HypercubeUnfolding,
+    pub extrusion: HypercubeExtrusion,
+    pub walk: HypercubeWalk,
+    pub spatial: SpatialBundle,
+}
+
+impl HypercubeBundle {
+    /// An `n` dimensional hypercube at rest, of the default size and style
+    pub fn new(n: usize) -> Self {
+        Self {
+            hypercube: Hypercube::new(n, SIZE.into()),
+            dimension: HypercubeDimension(n),
+            rotations: HypercubeRotations::new(n, &[]),
+            paused: HypercubePaused::default(),
+            style: HypercubeStyle::default(),
+            highlight: HypercubeHighlight::default(),
+            unfolding: HypercubeUnfolding::default(),
+            extrusion: HypercubeExtrusion::default(),
+            walk: HypercubeWalk::default(),
+            spatial: SpatialBundle::default(),
+        }
+    }
+}
+
+/// Components of a hypercube that the settings, data files and undo history work on
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct HypercubeComponents {
+    pub hypercube: &'static mut Hypercube,
+    pub dimension: &'static mut HypercubeDimension,
+    pub rotations: &'static mut HypercubeRotations,
+    pub paused: &'static mut HypercubePaused,
+    pub style: &'static mut HypercubeStyle,
+    pub highlight: &'static mut HypercubeHighlight,
+    pub unfolding: &'static mut HypercubeUnfolding,
+    pub extrusion: &'static mut HypercubeExtrusion,
+    pub walk: &'static mut HypercubeWalk,
+}
+
+/// Edge of a hypercube, spawned as a child of it
+#[derive(Component)]
+pub struct Edge {
+    /// Indices of its vertices in [`Hypercube::vertices_3d`]
+    pub vertices: (usize, usize),
+    /// Index of the edge in the edges of the [`Hypercube::ncube`]
+    pub index: usize,
+}
+/// Face of a hypercube, spawned as a child of it
+#[derive(Component)]
+pub struct Face {
+    plane: (usize, usize),
+    /// Indices of its vertices in [`Hypercube::vertices_3d`], in cyclic order
+    pub vertices: Vec<usize>,
+    /// Index of the face in the faces of the [`Hypercube::ncube`]
+    pub index: usize,
+    /// Color before blending
+    color: Color,
+}
+
+/// Picks a distinct hue for each of the planes of an `n` dimensional hypercube
+pub fn face_plane_color(plane: (usize, usize), n: usize, alpha: f32) -> Color {
+    let planes = usize::pair_permutations(0, n - 1);
+    let index = planes.iter().position(|p| *p == plane).unwrap_or(0);
+    Color::hsla(360.0 * index as f32 / planes.len() as f32, 0.9, 0.5, alpha)
+}
+
+fn face_color(plane: (usize, usize), n: usize, style: &HypercubeStyle) -> Color {
+    if style.face_color_by_plane {
+        face_plane_color(plane, n, style.face_color.a())
+    } else {
+        style.face_color
+    }
+}
+
+/// Alpha multiplier of everything outside of the highlighted cell when fading is enabled
+const FADED_ALPHA: f32 = 0.1;
+
+/// Recolors the element made of `vertices` based on whether it belongs to the highlighted cell
+fn highlight_color(color: Color, vertices: &[usize], highlight: &HypercubeHighlight) -> Color {
+    let Some(cell) = &highlight.cell else {
+        return color;
+    };
+    if vertices.iter().all(|i| cell.contains(*i)) {
+        highlight.color.with_a(color.a())
+    } else if highlight.fade_unhighlighted {
+        color.with_a(color.a() * FADED_ALPHA)
+    } else {
+        color
+    }
+}
+
+/// Color of the edge between the hypercube vertices `i` and `j`
+fn edge_color(
+    (i, j): (usize, usize),
+    style: &HypercubeStyle,
+    highlight: &HypercubeHighlight,
+    walk: (&HypercubeWalk, Option<&WalkedEdges>),
+) -> Color {
+    match walk {
+        (walk, Some(walked)) if walked.contains((i, j)) => walk.color,
+        _ => highlight_color(style.edge_color, &[i, j], highlight),
+    }
+}
+
+fn edge_alpha_mode(color: Color) -> AlphaMode {
+    if color.a() < 1.0 {
+        AlphaMode::Blend
+    } else {
+        AlphaMode::Opaque
+    }
+}
+
+fn face_alpha_mode(face_blend_mode: FaceBlendMode) -> AlphaMode {
+    match face_blend_mode {
+        FaceBlendMode::Additive => AlphaMode::Add,
+        FaceBlendMode::Sorted | FaceBlendMode::WeightedBlended => AlphaMode::Blend,
+    }
+}
+
+fn centroid(vertices: &[Vec3]) -> Vec3 {
+    vertices.iter().sum::<Vec3>() / vertices.len() as f32
+}
+
+/// Builds the mesh of a convex polygon, triangulated as a fan around its first vertex
+fn polygon_mesh(vertices: Vec<Vec3>, unlit: bool) -> Mesh {
+    let indices = (1..vertices.len() as u32 - 1)
+        .flat_map(|k| [0, k, k + 1])
+        .collect();
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_indices(Indices::U32(indices));
+    update_polygon_mesh(&mut mesh, vertices, unlit);
+    mesh
+}
+
+fn update_polygon_mesh(mesh: &mut Mesh, vertices: Vec<Vec3>, unlit: bool) {
+    if !unlit {
+        let normal = Vec3::polygon_normal(&vertices);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![normal; vertices.len()]);
+    }
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+}
+
+fn edge_visibility(projection: HypercubeProjection) -> Visibility {
+    if projection == HypercubeProjection::CrossSection {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    }
+}
+
+fn face_visibility(
+    plane: (usize, usize),
+    style: &HypercubeStyle,
+    projection: HypercubeProjection,
+) -> Visibility {
+    if style.isolate_face_planes && !style.selected_face_planes.contains(&plane) {
+        Visibility::Hidden
+    } else {
+        edge_visibility(projection)
+    }
+}
+
+/// Vertices of `face` relative to its centroid, and the centroid
+fn face_vertices(face: &[usize], vertices_3d: &[Vec3]) -> (Vec<Vec3>, Vec3) {
+    let vertices = face.iter().map(|i| vertices_3d[*i]).collect::<Vec<_>>();
+    let translation = centroid(&vertices);
+    (
+        vertices.iter().map(|v| *v - translation).collect(),
+        translation,
+    )
+}
+
+/// Hypercube vertices that the rendered `vertices` come from
+fn origins(vertices: &[usize], net: Option<&HypercubeNet>) -> Vec<usize> {
+    vertices
+        .iter()
+        .map(|i| HypercubeNet::origin(net, *i))
+        .collect()
+}
+
+fn edge_origins((i, j): (usize, usize), net: Option<&HypercubeNet>) -> (usize, usize) {
+    (HypercubeNet::origin(net, i), HypercubeNet::origin(net, j))
+}
+
+/// Projects `points` of the space of `ncube` into the 3D scene. The cross-section of a
+/// hypercube is drawn apart, and its points are seen in perspective.
+fn project(
+    ncube: &ncube_core::NCube,
+    points: &[Vec<f64>],
+    projection: HypercubeProjection,
+) -> Vec<Vec3> {
+    let points = match projection {
+        HypercubeProjection::Orthographic => ncube.orthographic_project(points),
+        _ => ncube.perspective_project(points),
+    };
+    to_scene(points)
+}
+
+fn to_scene(points: Vec<[f64; 3]>) -> Vec<Vec3> {
+    points
+        .into_iter()
+        .map(|point| bevy::math::DVec3::from_array(point).as_vec3())
+        .collect()
+}
+
+/// Projects the vertices of `ncube` into the 3D scene
+fn vertices_3d(ncube: &ncube_core::NCube, projection: HypercubeProjection) -> Vec<Vec3> {
+    project(ncube, &ncube.vertices.0, projection)
+}
+
+/// Projects the vertices of `net`, unfolded by `angle` and turned along with `ncube`, into the
+/// 3D scene. A net unfolded recursively is left as created instead, so that it ends up flat in
+/// 3D, while a net of a 5-cube and up that isn't is projected like the hypercube.
+fn net_vertices_3d(
+    ncube: &ncube_core::NCube,
+    net: &NNet,
+    angle: f64,
+    projection: HypercubeProjection,
+) -> Vec<Vec3> {
+    let vertices = net.unfold(angle);
+    if net.is_recursive() {
+        return project(ncube, &vertices, projection);
+    }
+    project(ncube, &ncube.orient(&vertices), projection)
+}
+
+/// Projects the vertices of `ncube` into the 3D scene with its last axis swept out by `progress`,
+/// the vertices on its negative side moving away from those on the positive side
+fn extruded_vertices_3d(
+    ncube: &ncube_core::NCube,
+    progress: f64,
+    projection: HypercubeProjection,
+) -> Vec<Vec3> {
+    let (n, h) = (ncube.dimensions, ncube.size / 2.0);
+    let points = (0..ncube.vertices.0.len())
+        .map(|i| {
+            (0..n)
+                .map(|d| match (i >> d & 1 == 1, d == n - 1) {
+                    (false, _) => h,
+                    (true, false) => -h,
+                    (true, true) => h - 2.0 * h * progress,
+                })
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    project(ncube, &ncube.orient(&points), projection)
+}
+
+#[allow(clippy::type_complexity)]
+fn rotate_hypercubes(
+    time: Res<Time>,
+    mut q_hypercubes: Query<(
+        &mut Hypercube,
+        &mut HypercubeDimension,
+        &mut HypercubeRotations,
+        &HypercubePaused,
+        &mut HypercubeUnfolding,
+        &mut HypercubeExtrusion,
+        Option<&HypercubeNet>,
+        Option<Ref<HypercubeProjection>>,
+    )>,
+) {
+    let dt: f64 = time.delta_seconds().into();
+    for (
+        mut hypercube,
+        mut dimension,
+        mut rotations,
+        paused,
+        mut unfolding,
+        mut extrusion,
+        net,
+        projection,
+    ) in &mut q_hypercubes
+    {
+        // Turned by another system, or seen through another projection
+        let is_reprojected =
+            hypercube.is_changed() || projection.as_ref().is_some_and(Ref::is_changed);
+        let projection = projection.map_or_else(default, |projection| *projection);
+        if unfolding.enabled {
+            if unfolding.velocity != 0.0 {
+                let angle = (unfolding.angle + dt * unfolding.velocity).clamp(0.0, FRAC_PI_2);
+                unfolding.angle = angle;
+                if angle == 0.0 || angle == FRAC_PI_2 {
+                    unfolding.velocity = 0.0;
+                }
+            }
+            let net = net.filter(|net| net.0.dimensions == hypercube.ncube.dimensions);
+            if let Some(net) = net.filter(|_| is_reprojected || unfolding.is_changed()) {
+                hypercube.vertices_3d =
+                    net_vertices_3d(&hypercube.ncube, &net.0, unfolding.angle, projection);
+            }
+            continue;
+        }
+        let mut is_moved = is_reprojected;
+        if !**paused {
+            let hypercube = &mut *hypercube;
+            let mut das = Vec::new();
+            for plane in &hypercube.planes_of_rotation {
+                let (angle, vel) = rotations.get(plane).copied().unwrap_or_default();
+                let da = dt * vel;
+                das.push(da);
+                rotations.insert(*plane, ((angle + da) % std::f64::consts::TAU, vel));
+            }
+            hypercube.ncube.rotate(&hypercube.planes_of_rotation, &das);
+            is_moved = true;
+        }
+        // A hypercube of a dimension above its own is flattened, to be rebuilt once flat
+        let is_flattening = hypercube.ncube.dimensions > **dimension;
+        let target = if is_flattening { 0.0 } else { 1.0 };
+        if extrusion.progress != target {
+            let step = dt * EXTRUSION_SPEED;
+            extrusion.progress = if is_flattening {
+                (extrusion.progress - step).max(target)
+            } else {
+                (extrusion.progress + step).min(target)
+            };
+            if is_flattening && extrusion.progress == target {
+                dimension.set_changed();
+            }
+            is_moved = true;
+        }
+        if is_moved {
+            hypercube.vertices_3d = if extrusion.progress < 1.0 {
+                extruded_vertices_3d(&hypercube.ncube, extrusion.progress, projection)
+            } else {
+                vertices_3d(&hypercube.ncube, projection)
+            };
+        }
+    }
+}
+
+/// Rebuilds the hypercubes whose dimension changed, or that were unfolded or folded back, along
+/// with their edges and faces
+#[allow(clippy::type_complexity)]
+fn spawn_hypercube_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut q_hypercubes: Query<
+        (
+            Entity,
+            Ref<HypercubeDimension>,
+            &mut Hypercube,
+            &mut HypercubeRotations,
+            &HypercubeStyle,
+            &mut HypercubeHighlight,
+            &HypercubeUnfolding,
+            &mut HypercubeExtrusion,
+            &mut HypercubeWalk,
+            Option<&WalkedEdges>,
+            Option<&HypercubeNet>,
+            Option<&HypercubeProjection>,
+            Option<&Children>,
+        ),
+        Or<(Changed<HypercubeDimension>, Changed<HypercubeUnfolding>)>,
+    >,
+    q_meshes: Query<(), Or<(With<Edge>, With<Face>)>>,
+) {
+    for (
+        entity,
+        dimension,
+        mut hypercube,
+        mut rotations,
+        style,
+        mut highlight,
+        unfolding,
+        mut extrusion,
+        mut walk,
+        walked,
+        net,
+        projection,
+        children,
+    ) in &mut q_hypercubes
+    {
+        let n = **dimension;
+        let is_recursive = unfolding.recursive && n > 4;
+        let is_net_current = match net {
+            Some(net) => unfolding.enabled && net.0.is_recursive() == is_recursive,
+            None => !unfolding.enabled,
+        };
+        if !dimension.is_changed() && is_net_current {
+            continue;
+        }
+        let projection = projection.copied().unwrap_or_default();
+        let m = hypercube.ncube.dimensions;
+        let is_animated = extrusion.enabled && !unfolding.enabled && !dimension.is_added();
+        // Swept out again, or flattened first and rebuilt once flat
+        if is_animated && (n == m || n + 1 == m && extrusion.progress > 0.0) {
+            continue;
+        }
+        extrusion.progress = if is_animated && n == m + 1 { 0.0 } else { 1.0 };
+        children
+            .into_iter()
+            .flatten()
+            .filter(|child| q_meshes.contains(**child))
+            .for_each(|child| commands.entity(*child).despawn_recursive());
+
+        // A hypercube that already has the dimension keeps its exact orientation
+        if hypercube.ncube.dimensions != n {
+            let planes_of_rotation = usize::pair_permutations(0, n - 1);
+            let mut new_rotations = HashMap::new();
+            let mut angles = Vec::new();
+            for plane in &planes_of_rotation {
+                let v = rotations.get(plane).copied().unwrap_or_default();
+                new_rotations.insert(*plane, v);
+                angles.push(v.0);
+            }
+            **rotations = new_rotations;
+            let mut ncube = ncube_core::NCube::new(n, hypercube.ncube.size);
+            ncube.rotate(&planes_of_rotation, &angles);
+            *hypercube = Hypercube {
+                vertices_3d: vertices_3d(&ncube, projection),
+                ncube,
+                planes_of_rotation,
+            };
+        }
+        let size = hypercube.ncube.size;
+        let net = unfolding.enabled.then(|| {
+            if is_recursive {
+                HypercubeNet(NNet::recursive(n, size))
+            } else {
+                HypercubeNet(NNet::new(n, size))
+            }
+        });
+        hypercube.vertices_3d = match &net {
+            Some(net) => net_vertices_3d(&hypercube.ncube, &net.0, unfolding.angle, projection),
+            None if extrusion.progress < 1.0 => {
+                extruded_vertices_3d(&hypercube.ncube, extrusion.progress, projection)
+            }
+            None => vertices_3d(&hypercube.ncube, projection),
+        };
+        let (edges, faces) = match &net {
+            Some(net) => (&net.0.edges.0, &net.0.faces.0),
+            None => (&hypercube.ncube.edges.0, &hypercube.ncube.faces.0),
+        };
+        if let Some(cell) = &highlight.cell {
+            if cell.fixed.len() > n || cell.fixed.iter().any(|(d, _)| *d >= n) {
+                highlight.cell = None;
+            }
+        }
+        if !walk.path.fits(n) {
+            walk.path = WalkPath::default();
+            walk.position = 0.0;
+        }
+
+        let mesh = Cuboid::default();
+        commands.entity(entity).with_children(|parent| {
+            for (e, (i, j)) in edges.iter().enumerate() {
+                let color = edge_color(
+                    edge_origins((*i, *j), net.as_ref()),
+                    style,
+                    &highlight,
+                    (&walk, walked),
+                );
+                parent.spawn((
+                    MaterialMeshBundle {
+                        mesh: meshes.add(mesh),
+                        material: materials.add(StandardMaterial {
+                            base_color: color,
+                            alpha_mode: edge_alpha_mode(color),
+                            double_sided: true,
+                            cull_mode: None,
+                            unlit: style.unlit,
+                            ..default()
+                        }),
+                        transform: edge::Edge::transform(
+                            style.edge_thickness,
+                            hypercube.vertices_3d[*i],
+                            hypercube.vertices_3d[*j],
+                        ),
+                        visibility: edge_visibility(projection),
+                        ..default()
+                    },
+                    Edge {
+                        vertices: (*i, *j),
+                        index: net.as_ref().map_or(e, |net| net.0.edge_origins[e]),
+                    },
+                ));
+            }
+            for (k, face) in faces.iter().enumerate() {
+                let face_origins = origins(face, net.as_ref());
+                let plane = hypercube.ncube.face_plane(&face_origins);
+                let color = highlight_color(face_color(plane, n, style), &face_origins, &highlight);
+                // Faces are placed at their centroid so that they are sorted by their own depth
+                let (vertices, translation) = face_vertices(face, &hypercube.vertices_3d);
+                parent.spawn((
+                    MaterialMeshBundle {
+                        mesh: meshes.add(polygon_mesh(vertices, style.unlit)),
+                        material: materials.add(StandardMaterial {
+                            base_color: color,
+                            alpha_mode: face_alpha_mode(style.face_blend_mode),
+                            double_sided: true,
+                            cull_mode: None,
+                            unlit: style.unlit,
+                            ..default()
+                        }),
+                        transform: Transform::from_translation(translation),
+                        visibility: face_visibility(plane, style, projection),
+                        ..default()
+                    },
+                    Face {
+                        plane,
+                        vertices: face.clone(),
+                        index: net.as_ref().map_or(k, |net| net.0.face_origins[k]),
+                        color,
+                    },
+                ));
+            }
+        });
+        match net {
+            Some(net) => commands.entity(entity).insert(net),
+            None => commands.entity(entity).remove::<HypercubeNet>(),
+        };
+    }
+}
+
+/// Builds a single mesh of the faces of `section`, whose vertices are at `vertices` in the scene
+fn section_mesh(section: &NSection, vertices: &[Vec3]) -> Mesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut indices = Vec::new();
+    for face in &section.faces.0 {
+        let polygon = face.iter().map(|i| vertices[*i]).collect::<Vec<_>>();
+        let start = positions.len() as u32;
+        indices
+            .extend((1..polygon.len() as u32 - 1).flat_map(|k| [start, start + k, start + k + 1]));
+        // A face cut right through one of its vertices has no area to face anywhere
+        let normal = Some(Vec3::polygon_normal(&polygon)).filter(|normal| normal.is_finite());
+        normals.extend(vec![normal.unwrap_or(Vec3::Z); polygon.len()]);
+        positions.extend(polygon);
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_indices(Indices::U32(indices))
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+}
+
+fn section_face_material(style: &HypercubeStyle) -> StandardMaterial {
+    StandardMaterial {
+        base_color: style.face_color,
+        alpha_mode: face_alpha_mode(style.face_blend_mode),
+        double_sided: true,
+        cull_mode: None,
+        unlit: style.unlit,
+        ..default()
+    }
+}
+
+fn section_edge_material(style: &HypercubeStyle) -> StandardMaterial {
+    StandardMaterial {
+        base_color: style.edge_color,
+        alpha_mode: edge_alpha_mode(style.edge_color),
+        unlit: style.unlit,
+        ..default()
+    }
+}
+
+/// Keeps the cross-section of the hypercubes seen in [`HypercubeProjection::CrossSection`] up to
+/// date, and despawns it once they are seen otherwise
+#[allow(clippy::type_complexity)]
+fn cut_hypercubes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    q_hypercubes: Query<(
+        Entity,
+        Ref<Hypercube>,
+        Ref<HypercubeStyle>,
+        Option<Ref<HypercubeProjection>>,
+        Option<&Children>,
+    )>,
+    mut q_section_edges: Query<
+        (&mut Transform, &Handle<StandardMaterial>),
+        (With<SectionEdge>, Without<SectionFaces>),
+    >,
+    q_section_faces: Query<(&Handle<Mesh>, &Handle<StandardMaterial>), With<SectionFaces>>,
+) {
+    for (entity, hypercube, style, projection, children) in &q_hypercubes {
+        let children = children.map_or(&[][..], |children| &**children);
+        let edges = children
+            .iter()
+            .filter(|child| q_section_edges.contains(**child))
+            .collect::<Vec<_>>();
+        let faces = children
+            .iter()
+            .find_map(|child| q_section_faces.get(*child).ok());
+        let Some(projection) =
+            projection.filter(|projection| **projection == HypercubeProjection::CrossSection)
+        else {
+            let faces = children
+                .iter()
+                .filter(|child| q_section_faces.contains(**child));
+            for child in edges.into_iter().chain(faces) {
+                commands.entity(*child).despawn_recursive();
+            }
+            continue;
+        };
+        let is_style_changed = style.is_changed() || projection.is_changed();
+        if !hypercube.is_changed() && !is_style_changed && faces.is_some() {
+            continue;
+        }
+
+        let section = hypercube.ncube.cross_section();
+        let vertices = to_scene(section.perspective_project_vertices());
+        let mesh = section_mesh(&section, &vertices);
+        match faces {
+            Some((mesh_handle, material_handle)) => {
+                *meshes.get_mut(mesh_handle).unwrap() = mesh;
+                if is_style_changed {
+                    *materials.get_mut(material_handle).unwrap() = section_face_material(&style);
+                }
+            }
+            None => {
+                commands.entity(entity).with_children(|parent| {
+                    parent.spawn((
+                        MaterialMeshBundle {
+                            mesh: meshes.add(mesh),
+                            material: materials.add(section_face_material(&style)),
+                            ..default()
+                        },
+                        SectionFaces,
+                    ));
+                });
+            }
+        }
+        for (k, (i, j)) in section.edges.0.iter().enumerate() {
+            let transform = edge::Edge::transform(style.edge_thickness, vertices[*i], vertices[*j]);
+            match edges.get(k) {
+                Some(child) => {
+                    let (mut edge_transform, material_handle) =
+                        q_section_edges.get_mut(**child).unwrap();
+                    *edge_transform = transform;
+                    if is_style_changed {
+                        *materials.get_mut(material_handle).unwrap() =
+                            section_edge_material(&style);
+                    }
+                }
+                None => {
+                    commands.entity(entity).with_children(|parent| {
+                        parent.spawn((
+                            MaterialMeshBundle {
+                                mesh: meshes.add(Cuboid::default()),
+                                material: materials.add(section_edge_material(&style)),
+                                transform,
+                                ..default()
+                            },
+                            SectionEdge,
+                        ));
+                    });
+                }
+            }
+        }
+        for child in edges.iter().skip(section.edges.0.len()) {
+            commands.entity(**child).despawn_recursive();
+        }
+    }
+}
+
+/// Gives the edges and faces the `RenderLayers` of their hypercube, so that it is only seen by
+/// the cameras of those layers. Weighted blended faces are on none, only their copies in the
+/// weighted blended passes are seen.
+#[allow(clippy::type_complexity)]
+fn propagate_render_layers(
+    mut commands: Commands,
+    q_hypercubes: Query<
+        (Option<&RenderLayers>, &HypercubeStyle, &Children),
+        (
+            With<Hypercube>,
+            Or<(
+                Changed<RenderLayers>,
+                Changed<HypercubeStyle>,
+                Changed<Children>,
+            )>,
+        ),
+    >,
+    q_meshes: Query<
+        (Option<&RenderLayers>, Has<Face>, Has<SectionFaces>),
+        Or<(
+            With<Edge>,
+            With<Face>,
+            With<WalkMarker>,
+            With<VertexMarker>,
+            With<SectionEdge>,
+            With<SectionFaces>,
+        )>,
+    >,
+) {
+    for (render_layers, style, children) in &q_hypercubes {
+        let render_layers = render_layers.copied().unwrap_or_default();
+        let is_weighted = style.face_blend_mode == FaceBlendMode::WeightedBlended;
+        for child in children {
+            if let Ok((child_render_layers, is_face, is_section_face)) = q_meshes.get(*child) {
+                let render_layers = if is_weighted && (is_face || is_section_face) {
+                    RenderLayers::none()
+                } else {
+                    render_layers
+                };
+                if child_render_layers != Some(&render_layers) {
+                    commands.entity(*child).insert(render_layers);
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_hypercube_meshes(
+    q_hypercubes: Query<(
+        Ref<Hypercube>,
+        &HypercubeDimension,
+        Ref<HypercubeStyle>,
+        Ref<HypercubeHighlight>,
+        (&HypercubeWalk, Option<&WalkedEdges>),
+        Option<&HypercubeNet>,
+        Option<Ref<HypercubeProjection>>,
+        &Children,
+    )>,
+    mut q_edges: Query<
+        (
+            &Edge,
+            &mut Transform,
+            &mut Visibility,
+            &Handle<StandardMaterial>,
+        ),
+        Without<Face>,
+    >,
+    mut q_faces: Query<
+        (
+            &mut Face,
+            &mut Transform,
+            &mut Visibility,
+            &Handle<Mesh>,
+            &Handle<StandardMaterial>,
+        ),
+        Without<Edge>,
+    >,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (hypercube, dimension, style, highlight, walk, net, projection, children) in &q_hypercubes {
+        let is_style_changed = style.is_changed()
+            || highlight.is_changed()
+            || projection.as_ref().is_some_and(Ref::is_changed);
+        let projection = projection.map_or_else(default, |projection| *projection);
+        let is_geometry_changed = hypercube.is_changed() || style.is_changed();
+        if !is_style_changed && !is_geometry_changed {
+            continue;
+        }
+        for child in children {
+            if let Ok((edge, mut transform, mut visibility, material_handle)) =
+                q_edges.get_mut(*child)
+            {
+                if is_style_changed {
+                    visibility.set_if_neq(edge_visibility(projection));
+                    let material = materials.get_mut(material_handle).unwrap();
+                    material.base_color =
+                        edge_color(edge_origins(edge.vertices, net), &style, &highlight, walk);
+                    material.alpha_mode = edge_alpha_mode(material.base_color);
+                    material.unlit = style.unlit;
+                }
+                if is_geometry_changed {
+                    *transform = edge::Edge::transform(
+                        style.edge_thickness,
+                        hypercube.vertices_3d[edge.vertices.0],
+                        hypercube.vertices_3d[edge.vertices.1],
+                    );
+                }
+            }
+            if let Ok((mut face, mut transform, mut visibility, mesh_handle, material_handle)) =
+                q_faces.get_mut(*child)
+            {
+                if is_style_changed {
+                    face.color = highlight_color(
+                        face_color(face.plane, **dimension, &style),
+                        &origins(&face.vertices, net),
+                        &highlight,
+                    );
+                    let material = materials.get_mut(material_handle).unwrap();
+                    material.base_color = face.color;
+                    material.alpha_mode = face_alpha_mode(style.face_blend_mode);
+                    material.unlit = style.unlit;
+                    *visibility = face_visibility(face.plane, &style, projection);
+                }
+                if is_geometry_changed {
+                    let (vertices, translation) =
+                        face_vertices(&face.vertices, &hypercube.vertices_3d);
+                    transform.translation = translation;
+                    update_polygon_mesh(
+                        meshes.get_mut(mesh_handle).unwrap(),
+                        vertices,
+                        style.unlit,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Point of the scene a `t` fraction of the way from hypercube vertex `a` to vertex `b`, along a
+/// copy of their edge while the hypercube is unfolded
+fn walk_marker_translation(
+    hypercube: &Hypercube,
+    net: Option<&HypercubeNet>,
+    (a, b): (usize, usize),
+    t: f32,
+) -> Vec3 {
+    let (i, j) = match net {
+        Some(net) => {
+            let origins = &net.0.origins;
+            net.0
+                .edges
+                .0
+                .iter()
+                .flat_map(|(i, j)| [(*i, *j), (*j, *i)])
+                .find(|(i, j)| (origins[*i], origins[*j]) == (a, b))
+                .unwrap_or_else(|| {
+                    let i = origins.iter().position(|o| *o == a).unwrap_or_default();
+                    (i, i)
+                })
+        }
+        None => (a, b),
+    };
+    hypercube.vertices_3d[i].lerp(hypercube.vertices_3d[j], t)
+}
+
+/// Moves the walk markers along their paths and highlights the edges they traversed
+#[allow(clippy::type_complexity)]
+fn walk_hypercubes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut q_hypercubes: Query<(
+        Entity,
+        &Hypercube,
+        &HypercubeStyle,
+        &HypercubeHighlight,
+        &mut HypercubeWalk,
+        Option<&mut WalkedEdges>,
+        Option<&HypercubeNet>,
+        Option<&HypercubeProjection>,
+        Option<Ref<Children>>,
+    )>,
+    q_edges: Query<(&Edge, &Handle<StandardMaterial>)>,
+    mut q_markers: MarkerQuery<WalkMarker>,
+) {
+    let dt: f64 = time.delta_seconds().into();
+    for (entity, hypercube, style, highlight, mut walk, walked, net, projection, children) in
+        &mut q_hypercubes
+    {
+        let vertices = walk.path.vertices(hypercube.ncube.dimensions);
+        let edges = vertices.len() - 1;
+        if walk.enabled && edges > 0 {
+            walk.position = (walk.position + dt * walk.speed) % edges as f64;
+        }
+        let step = (walk.position as usize).min(edges);
+        let new_walked = WalkedEdges(if walk.enabled {
+            vertices
+                .windows(2)
+                .take(step)
+                .map(|w| (w[0].min(w[1]), w[0].max(w[1])))
+                .collect()
+        } else {
+            HashSet::new()
+        });
+        let Some(children) = children else {
+            continue;
+        };
+
+        if walked.as_deref() != Some(&new_walked) || children.is_changed() {
+            for (edge, material_handle) in q_edges.iter_many(&*children) {
+                let material = materials.get_mut(material_handle).unwrap();
+                material.base_color = edge_color(
+                    edge_origins(edge.vertices, net),
+                    style,
+                    highlight,
+                    (&walk, Some(&new_walked)),
+                );
+                material.alpha_mode = edge_alpha_mode(material.base_color);
+            }
+        }
+        match walked {
+            Some(mut walked) if *walked != new_walked => *walked = new_walked,
+            Some(_) => {}
+            None => {
+                commands.entity(entity).insert(new_walked);
+            }
+        }
+
+        let edge = (vertices[step], vertices[(step + 1).min(edges)]);
+        let t = (walk.position - step as f64) as f32;
+        let transform = Transform {
+            translation: walk_marker_translation(hypercube, net, edge, t),
+            scale: Vec3::splat(3.0 * style.edge_thickness),
+            ..default()
+        };
+        let is_shown = walk.enabled && projection != Some(&HypercubeProjection::CrossSection);
+        let marker = is_shown.then_some((WalkMarker, transform, walk.color));
+        update_marker(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &mut q_markers,
+            (entity, &children),
+            marker,
+        );
+    }
+}
+
+/// Marks the vertex highlighted on its own, which has no edge or face to stand out
+#[allow(clippy::type_complexity)]
+fn mark_highlighted_vertices(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    q_hypercubes: Query<(
+        Entity,
+        &Hypercube,
+        &HypercubeStyle,
+        &HypercubeHighlight,
+        Option<&HypercubeNet>,
+        Option<&HypercubeProjection>,
+        &Children,
+    )>,
+    mut q_markers: MarkerQuery<VertexMarker>,
+) {
+    for (entity, hypercube, style, highlight, net, projection, children) in &q_hypercubes {
+        let n = hypercube.ncube.dimensions;
+        let vertex = highlight
+            .cell
+            .as_ref()
+            .filter(|cell| cell.fixed.len() == n)
+            .filter(|_| projection != Some(&HypercubeProjection::CrossSection))
+            .and_then(|cell| {
+                (0..hypercube.vertices_3d.len())
+                    .find(|i| cell.contains(HypercubeNet::origin(net, *i)))
+            });
+        let marker = vertex.map(|i| {
+            let transform = Transform {
+                translation: hypercube.vertices_3d[i],
+                scale: Vec3::splat(4.0 * style.edge_thickness),
+                ..default()
+            };
+            (VertexMarker, transform, highlight.color)
+        });
+        update_marker(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &mut q_markers,
+            (entity, children),
+            marker,
+        );
+    }
+}
+
+type MarkerQuery<'w, 's, M> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        &'static mut Visibility,
+        &'static Handle<StandardMaterial>,
+    ),
+    With<M>,
+>;
+
+/// Shows the sphere `marker` of a hypercube with the given transform and color, spawning it the
+/// first time, or hides it when there is none
+fn update_marker<M: Component>(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    q_markers: &mut MarkerQuery<M>,
+    (entity, children): (Entity, &Children),
+    marker: Option<(M, Transform, Color)>,
+) {
+    let spawned = children.iter().find(|child| q_markers.contains(**child));
+    match (spawned, marker) {
+        (Some(spawned), marker) => {
+            let (mut transform, mut visibility, material_handle) =
+                q_markers.get_mut(*spawned).unwrap();
+            let new_visibility = match &marker {
+                Some((_, new_transform, color)) => {
+                    *transform = *new_transform;
+                    if materials.get(material_handle).unwrap().base_color != *color {
+                        materials.get_mut(material_handle).unwrap().base_color = *color;
+                    }
+                    Visibility::Inherited
+                }
+                None => Visibility::Hidden,
+            };
+            if *visibility != new_visibility {
+                *visibility = new_visibility;
+            }
+        }
+        (None, Some((marker, transform, color))) => {
+            commands.entity(entity).with_children(|parent| {
+                parent.spawn((
+                    PbrBundle {
+                        mesh: meshes.add(Sphere::default()),
+                        material: materials.add(StandardMaterial {
+                            base_color: color,
+                            unlit: true,
+                            ..default()
+                        }),
+                        transform,
+                        ..default()
+                    },
+                    marker,
+                ));
+            });
+        }
+        (None, None) => {}
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use bevy::app::Plugins;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+    /// Headless app running [`NCubePlugin`] and the other given plugins
+    pub fn app<M>(plugins: impl Plugins<M>) -> App {
+        let mut app = App::new();
+        // The shaders of the weighted blended faces are loaded as the plugin is built
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Shader>()
+            .add_plugins(NCubePlugin)
+            .add_plugins(plugins)
+            .init_asset::<Mesh>()
+            .init_asset::<Image>()
+            .init_asset::<StandardMaterial>();
+        app
+    }
+    #[test]
+    fn rebuild_on_dimension_change() {
+        let mut app = app(());
+        let mut bundle = HypercubeBundle::new(4);
+        bundle.rotations = HypercubeRotations::new(4, &[((0, 3), 0.5)]);
+        let entity = app.world.spawn(bundle).id();
+        app.update();
+        let children = app.world.get::<Children>(entity).unwrap();
+        assert_eq!(children.len(), 32 + 24);
+
+        app.world.get_mut::<HypercubeDimension>(entity).unwrap().0 = 5;
+        app.update();
+        let hypercube = app.world.get::<Hypercube>(entity).unwrap();
+        assert_eq!(hypercube.ncube.dimensions, 5);
+        assert_eq!(hypercube.vertices_3d.len(), 32);
+        let rotations = app.world.get::<HypercubeRotations>(entity).unwrap();
+        assert_eq!(rotations.len(), 10);
+        assert_eq!(rotations[&(0, 3)].1, 0.5);
+        let children = app.world.get::<Children>(entity).unwrap();
+        assert_eq!(children.len(), 80 + 80);
+    }
+    #[test]
+    fn unfold_and_fold_back() {
+        let mut app = app(());
+        let entity = app.world.spawn(HypercubeBundle::new(4)).id();
+        app.update();
+
+        *app.world.get_mut::<HypercubeUnfolding>(entity).unwrap() = HypercubeUnfolding {
+            enabled: true,
+            angle: FRAC_PI_2,
+            velocity: 1.0,
+            recursive: false,
+        };
+        app.update();
+        let unfolding = app.world.get::<HypercubeUnfolding>(entity).unwrap();
+        assert_eq!(unfolding.angle, FRAC_PI_2);
+        assert_eq!(unfolding.velocity, 0.0);
+        // The 8 cubes of the Dalí cross, all in the hyperplane projected at half scale
+        let hypercube = app.world.get::<Hypercube>(entity).unwrap();
+        assert_eq!(hypercube.vertices_3d.len(), 8 * 8);
+        assert!(hypercube
+            .vertices_3d
+            .iter()
+            .all(|v| v.max_element() <= 5.0 * SIZE / 4.0 + 1e-5));
+        let children = app.world.get::<Children>(entity).unwrap();
+        assert_eq!(children.len(), 8 * (12 + 6));
+
+        app.world
+            .get_mut::<HypercubeUnfolding>(entity)
+            .unwrap()
+            .enabled = false;
+        app.update();
+        let hypercube = app.world.get::<Hypercube>(entity).unwrap();
+        assert_eq!(hypercube.vertices_3d.len(), 16);
+        let children = app.world.get::<Children>(entity).unwrap();
+        assert_eq!(children.len(), 32 + 24);
+    }
+    #[test]
+    fn unfold_recursively() {
+        let mut app = app(());
+        let entity = app.world.spawn(HypercubeBundle::new(5)).id();
+        app.update();
+
+        *app.world.get_mut::<HypercubeUnfolding>(entity).unwrap() = HypercubeUnfolding {
+            enabled: true,
+            angle: FRAC_PI_2,
+            velocity: 0.0,
+            recursive: false,
+        };
+        app.update();
+        let children = app.world.get::<Children>(entity).unwrap();
+        assert_eq!(children.len(), 10 * (32 + 24));
+
+        // Rebuilt from the 80 cubes of the facets' nets
+        app.world
+            .get_mut::<HypercubeUnfolding>(entity)
+            .unwrap()
+            .recursive = true;
+        app.update();
+        let hypercube = app.world.get::<Hypercube>(entity).unwrap();
+        assert_eq!(hypercube.vertices_3d.len(), 80 * 8);
+        assert!(app
+            .world
+            .get::<HypercubeNet>(entity)
+            .unwrap()
+            .0
+            .is_recursive());
+        let children = app.world.get::<Children>(entity).unwrap();
+        assert_eq!(children.len(), 80 * (12 + 6));
+    }
+    #[test]
+    fn extrude_and_flatten() {
+        let mut app = app(());
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            250,
+        )));
+        let mut bundle = HypercubeBundle::new(4);
+        bundle.extrusion.enabled = true;
+        let entity = app.world.spawn(bundle).id();
+        app.update();
+        let progress = |app: &App| {
+            app.world
+                .get::<HypercubeExtrusion>(entity)
+                .unwrap()
+                .progress
+        };
+        let dimensions = |app: &App| app.world.get::<Hypercube>(entity).unwrap().ncube.dimensions;
+
+        app.world.get_mut::<HypercubeDimension>(entity).unwrap().0 = 5;
+        app.update();
+        // Rebuilt flat, the two copies of the tesseract on top of each other
+        assert_eq!(progress(&app), 0.0);
+        assert_eq!(dimensions(&app), 5);
+        let hypercube = app.world.get::<Hypercube>(entity).unwrap();
+        assert!((0..16).all(|i| hypercube.vertices_3d[i] == hypercube.vertices_3d[i | 16]));
+        app.update();
+        assert!(progress(&app) > 0.0);
+        (0..8).for_each(|_| app.update());
+        assert_eq!(progress(&app), 1.0);
+
+        // Flattened first, then rebuilt
+        app.world.get_mut::<HypercubeDimension>(entity).unwrap().0 = 4;
+        app.update();
+        assert!(progress(&app) < 1.0);
+        assert_eq!(dimensions(&app), 5);
+        (0..8).for_each(|_| app.update());
+        assert_eq!(progress(&app), 1.0);
+        assert_eq!(dimensions(&app), 4);
+        let children = app.world.get::<Children>(entity).unwrap();
+        assert_eq!(children.len(), 32 + 24);
+    }
+    #[test]
+    fn walk_gray_code() {
+        let mut app = app(());
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            250,
+        )));
+        let mut bundle = HypercubeBundle::new(3);
+        bundle.walk = HypercubeWalk {
+            enabled: true,
+            speed: 10.0,
+            ..default()
+        };
+        let entity = app.world.spawn(bundle).id();
+        (0..3).for_each(|_| app.update());
+
+        let walk = app.world.get::<HypercubeWalk>(entity).unwrap();
+        let step = walk.position as usize;
+        assert!(step > 0);
+        // 000, 001, 011, 010, 110, 111, 101, 100
+        let walked = app.world.get::<WalkedEdges>(entity).unwrap();
+        assert_eq!(walked.0.len(), step);
+        assert!(walked.contains((0b011, 0b001)));
+        let color = walk.color;
+        let children = app.world.get::<Children>(entity).unwrap();
+        let walked_colors = children
+            .iter()
+            .filter_map(|child| app.world.get::<Edge>(*child).map(|edge| (child, edge)))
+            .filter(|(_, edge)| walked.contains(edge.vertices))
+            .map(|(child, _)| {
+                let handle = app.world.get::<Handle<StandardMaterial>>(*child).unwrap();
+                let materials = app.world.resource::<Assets<StandardMaterial>>();
+                materials.get(handle).unwrap().base_color
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(walked_colors, vec![color; step]);
+        assert!(children
+            .iter()
+            .any(|child| app.world.get::<WalkMarker>(*child).is_some()));
+    }
+}
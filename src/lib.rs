@@ -0,0 +1,56 @@
+//! Rotating hypercubes for Bevy apps, and the pieces of the ncube viewer built on them.
+//!
+//! [`NCubePlugin`] renders every entity spawned with a [`HypercubeBundle`], whose components
+//! hold its dimension, rotations and style:
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use ncube::{HypercubeBundle, HypercubeRotations, NCubePlugin};
+//!
+//! App::new()
+//!     .add_plugins((DefaultPlugins, NCubePlugin))
+//!     .add_systems(Startup, |mut commands: Commands| {
+//!         commands.spawn(Camera3dBundle {
+//!             transform: Transform::from_xyz(0.0, 0.0, 4.0),
+//!             ..default()
+//!         });
+//!         commands.spawn(HypercubeBundle {
+//!             rotations: HypercubeRotations::new(4, &[((0, 3), 0.5)]),
+//!             ..HypercubeBundle::new(4)
+//!         });
+//!     })
+//!     .run();
+//! ```
+//!
+//! The settings window ([`settings::SettingsPlugin`]), the orbit camera
+//! ([`camera::CameraPlugin`]), the overlay text ([`text::TextPlugin`]), the split screen
+//! ([`views::ViewsPlugin`]) and the mouse picking ([`picking::PickingPlugin`]) are opt-in. The
+//! settings, text and picking work on the [`resources::SelectedHypercube`].
+
+pub mod camera;
+#[cfg(not(target_family = "wasm"))]
+mod config;
+pub mod data;
+mod edge;
+mod gallery;
+pub mod history;
+pub mod hypercube;
+mod oit;
+pub mod picking;
+pub mod resources;
+pub mod scene;
+pub mod session;
+pub mod settings;
+pub mod text;
+mod vec;
+pub mod views;
+
+pub use hypercube::{
+    Hypercube, HypercubeBundle, HypercubeDimension, HypercubeHighlight, HypercubePaused,
+    HypercubeProjection, HypercubeRotations, HypercubeStyle, HypercubeUnfolding, NCubePlugin,
+    NCubeSystems,
+};
+
+pub fn version_info() -> String {
+    format!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+}
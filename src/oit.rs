@@ -0,0 +1,612 @@
+//! Weighted blended order-independent transparency (McGuire and Bavoil, 2013), for the faces of
+//! the hypercubes blended with [`FaceBlendMode::WeightedBlended`]
+//!
+//! Each view gets two cameras of its own, which see copies of those faces on render layers
+//! shifted past those of the views. One adds up their weighted colors in an accumulation
+//! target, the other multiplies together how much of the background they let through in a
+//! revealage target. Copies of the edges and markers only write depth, so that they still hide
+//! the faces behind them. A quad covering the view then composites both targets over it.
+
+use crate::hypercube::{
+    Edge, Face, Hypercube, HypercubeStyle, NCubeSystems, SectionEdge, SectionFaces, VertexMarker,
+    WalkMarker,
+};
+use crate::resources::FaceBlendMode;
+use bevy::asset::load_internal_asset;
+use bevy::core_pipeline::tonemapping::{DebandDither, Tonemapping};
+use bevy::pbr::{
+    ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+    MaterialPipeline, MaterialPipelineKey, MeshPipelineKey, NotShadowCaster,
+};
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::mesh::MeshVertexBufferLayout;
+use bevy::render::render_resource::{
+    AsBindGroup, BlendComponent, BlendFactor, BlendOperation, BlendState, ColorWrites,
+    CompareFunction, Extent3d, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::render::texture::BevyDefault;
+use bevy::render::view::{NoFrustumCulling, RenderLayers};
+use bevy::transform::TransformSystem;
+use bevy::utils::HashSet;
+
+const WEIGHTED_FACE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x5f1c_2a4e_8b3d_4c6f_9e07_a1d2_b3c4_d5e6);
+const OCCLUDER_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x6a2d_3b5f_9c4e_4d70_af18_b2e3_c4d5_e6f7);
+const COMPOSITE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x7b3e_4c60_ad5f_4e81_b029_c3f4_d5e6_f708);
+
+/// The weighted blended passes of a view see the copies on its render layers shifted by this
+/// much, so only the layers below it are blended
+const LAYER_OFFSET: u8 = 16;
+
+/// Renders the faces of the hypercubes blended with [`FaceBlendMode::WeightedBlended`]
+pub struct WeightedBlendedPlugin;
+
+impl Plugin for WeightedBlendedPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            WEIGHTED_FACE_SHADER_HANDLE,
+            "../assets/shaders/weighted_face.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            OCCLUDER_SHADER_HANDLE,
+            "../assets/shaders/occluder.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            COMPOSITE_SHADER_HANDLE,
+            "../assets/shaders/composite.wgsl",
+            Shader::from_wgsl
+        );
+        app.add_plugins((
+            MaterialPlugin::<WeightedFaceMaterial> {
+                prepass_enabled: false,
+                ..default()
+            },
+            MaterialPlugin::<OccluderMaterial> {
+                prepass_enabled: false,
+                ..default()
+            },
+            MaterialPlugin::<CompositeMaterial> {
+                prepass_enabled: false,
+                ..default()
+            },
+        ))
+        .add_systems(
+            PostUpdate,
+            (
+                copy_blended_meshes,
+                update_blended_meshes,
+                update_blended_views,
+            )
+                .chain()
+                .after(NCubeSystems)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+type WeightedFaceMaterial = ExtendedMaterial<StandardMaterial, WeightedFaceExtension>;
+
+/// Lights a face like a [`StandardMaterial`], then writes it to the target of the pass seeing
+/// it: the accumulation target is HDR, the revealage one is not
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+struct WeightedFaceExtension {}
+
+impl MaterialExtension for WeightedFaceExtension {
+    fn fragment_shader() -> ShaderRef {
+        WEIGHTED_FACE_SHADER_HANDLE.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let Some(fragment) = descriptor.fragment.as_mut() else {
+            return Ok(());
+        };
+        let blend = if key.mesh_key.contains(MeshPipelineKey::HDR) {
+            fragment.shader_defs.push("ACCUMULATION".into());
+            BlendComponent {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            }
+        } else {
+            BlendComponent {
+                src_factor: BlendFactor::Zero,
+                dst_factor: BlendFactor::OneMinusSrc,
+                operation: BlendOperation::Add,
+            }
+        };
+        for target in fragment.targets.iter_mut().flatten() {
+            target.blend = Some(BlendState {
+                color: blend,
+                alpha: blend,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn weighted_face_material(material: &StandardMaterial) -> WeightedFaceMaterial {
+    ExtendedMaterial {
+        base: StandardMaterial {
+            base_color: material.base_color,
+            alpha_mode: AlphaMode::Blend,
+            double_sided: true,
+            cull_mode: None,
+            unlit: material.unlit,
+            ..default()
+        },
+        extension: WeightedFaceExtension {},
+    }
+}
+
+/// Writes the depth of an edge or a marker and nothing else
+#[derive(Asset, AsBindGroup, TypePath, Debug, Clone)]
+struct OccluderMaterial {}
+
+impl Material for OccluderMaterial {
+    fn fragment_shader() -> ShaderRef {
+        OCCLUDER_SHADER_HANDLE.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            for target in fragment.targets.iter_mut().flatten() {
+                target.write_mask = ColorWrites::empty();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Blends the faces accumulated in the weighted blended passes of a view over it
+#[derive(Asset, AsBindGroup, TypePath, Debug, Clone)]
+struct CompositeMaterial {
+    #[texture(0)]
+    accumulation: Handle<Image>,
+    #[texture(1)]
+    revealage: Handle<Image>,
+}
+
+impl Material for CompositeMaterial {
+    fn vertex_shader() -> ShaderRef {
+        COMPOSITE_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        COMPOSITE_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+
+    /// Drawn after everything else in the view, which it covers regardless of depth
+    fn depth_bias(&self) -> f32 {
+        f32::MAX
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = None;
+        if let Some(depth_stencil) = descriptor.depth_stencil.as_mut() {
+            depth_stencil.depth_compare = CompareFunction::Always;
+            depth_stencil.depth_write_enabled = false;
+        }
+        Ok(())
+    }
+}
+
+/// Copy of the faces of a hypercube, or of its cross-section, seen in the weighted blended
+/// passes instead of them
+#[derive(Component)]
+struct WeightedFace;
+
+/// Copy of an edge or a marker of a hypercube, which hides the faces behind it in the weighted
+/// blended passes
+#[derive(Component)]
+struct Occluder;
+
+/// Camera of a weighted blended pass, spawned as a child of the camera of its view
+#[derive(Component)]
+struct BlendedCamera;
+
+/// Quad compositing the weighted blended passes of `view`, seen by `cameras` in targets of
+/// `size`
+#[derive(Component)]
+struct Composite {
+    view: Entity,
+    cameras: [Entity; 2],
+    size: UVec2,
+}
+
+fn is_weighted_blended(style: &HypercubeStyle) -> bool {
+    style.face_blend_mode == FaceBlendMode::WeightedBlended
+}
+
+/// Layers of the weighted blended passes of the views on `layers`
+fn blended_layers(layers: Option<&RenderLayers>) -> RenderLayers {
+    let layers = layers
+        .copied()
+        .unwrap_or_default()
+        .iter()
+        .map(|layer| layer + LAYER_OFFSET)
+        .filter(|layer| (*layer as usize) < RenderLayers::TOTAL_LAYERS)
+        .collect::<Vec<_>>();
+    RenderLayers::from_layers(&layers)
+}
+
+fn target_image(size: UVec2, format: TextureFormat) -> Image {
+    let size = Extent3d {
+        width: size.x,
+        height: size.y,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+/// Spawns the copies of the faces, edges and markers of the weighted blended hypercubes, and
+/// despawns them once the hypercubes are blended otherwise
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn copy_blended_meshes(
+    mut commands: Commands,
+    materials: Res<Assets<StandardMaterial>>,
+    mut weighted_materials: ResMut<Assets<WeightedFaceMaterial>>,
+    mut occluder_materials: ResMut<Assets<OccluderMaterial>>,
+    mut occluder_material: Local<Option<Handle<OccluderMaterial>>>,
+    q_hypercubes: Query<
+        (&HypercubeStyle, Option<&RenderLayers>, &Children),
+        (
+            With<Hypercube>,
+            Or<(Changed<HypercubeStyle>, Changed<Children>)>,
+        ),
+    >,
+    q_meshes: Query<
+        (
+            &Handle<Mesh>,
+            &Handle<StandardMaterial>,
+            Has<Face>,
+            Has<SectionFaces>,
+            Option<&Children>,
+        ),
+        Or<(
+            With<Face>,
+            With<SectionFaces>,
+            With<Edge>,
+            With<SectionEdge>,
+            With<WalkMarker>,
+            With<VertexMarker>,
+        )>,
+    >,
+    q_copies: Query<(), Or<(With<WeightedFace>, With<Occluder>)>>,
+) {
+    for (style, layers, children) in &q_hypercubes {
+        let is_weighted = is_weighted_blended(style);
+        for child in children {
+            let Ok((mesh, material_handle, is_face, is_section_face, mesh_children)) =
+                q_meshes.get(*child)
+            else {
+                continue;
+            };
+            let copies = mesh_children
+                .into_iter()
+                .flatten()
+                .filter(|copy| q_copies.contains(**copy))
+                .collect::<Vec<_>>();
+            if !is_weighted {
+                for copy in copies {
+                    commands.entity(*copy).despawn_recursive();
+                }
+                continue;
+            }
+            let Some(material) = materials.get(material_handle).filter(|_| copies.is_empty())
+            else {
+                continue;
+            };
+            let copy = (
+                mesh.clone(),
+                SpatialBundle::default(),
+                blended_layers(layers),
+                NotShadowCaster,
+            );
+            if is_face || is_section_face {
+                let material = weighted_materials.add(weighted_face_material(material));
+                commands.entity(*child).with_children(|parent| {
+                    parent.spawn((copy, material, WeightedFace));
+                });
+            } else {
+                let material = occluder_material
+                    .get_or_insert_with(|| occluder_materials.add(OccluderMaterial {}))
+                    .clone();
+                commands.entity(*child).with_children(|parent| {
+                    parent.spawn((copy, material, Occluder));
+                });
+            }
+        }
+    }
+}
+
+/// Keeps the copies of the faces, edges and markers of the weighted blended hypercubes in step
+/// with them and on the layers of the weighted blended passes of their views. Only the opaque
+/// edges and markers hide the faces behind them.
+#[allow(clippy::type_complexity)]
+fn update_blended_meshes(
+    materials: Res<Assets<StandardMaterial>>,
+    mut weighted_materials: ResMut<Assets<WeightedFaceMaterial>>,
+    q_hypercubes: Query<(&HypercubeStyle, Option<&RenderLayers>, &Children), With<Hypercube>>,
+    q_meshes: Query<(&Handle<StandardMaterial>, &Children)>,
+    mut q_copies: Query<
+        (
+            &mut RenderLayers,
+            &mut Visibility,
+            Option<&Handle<WeightedFaceMaterial>>,
+        ),
+        (Or<(With<WeightedFace>, With<Occluder>)>, Without<Hypercube>),
+    >,
+) {
+    for (style, layers, children) in &q_hypercubes {
+        if !is_weighted_blended(style) {
+            continue;
+        }
+        let layers = blended_layers(layers);
+        for (material_handle, mesh_children) in q_meshes.iter_many(children) {
+            let Some(material) = materials.get(material_handle) else {
+                continue;
+            };
+            let mut copies = q_copies.iter_many_mut(mesh_children);
+            while let Some((mut copy_layers, mut visibility, weighted_handle)) = copies.fetch_next()
+            {
+                copy_layers.set_if_neq(layers);
+                let Some(weighted_handle) = weighted_handle else {
+                    visibility.set_if_neq(match material.alpha_mode {
+                        AlphaMode::Opaque => Visibility::Inherited,
+                        _ => Visibility::Hidden,
+                    });
+                    continue;
+                };
+                let weighted = weighted_face_material(material);
+                let is_changed = weighted_materials
+                    .get(weighted_handle)
+                    .is_some_and(|current| {
+                        current.base.base_color != weighted.base.base_color
+                            || current.base.unlit != weighted.base.unlit
+                    });
+                if is_changed {
+                    *weighted_materials.get_mut(weighted_handle).unwrap() = weighted;
+                }
+            }
+        }
+    }
+}
+
+/// Gives each view the cameras of its weighted blended passes and the quad compositing them,
+/// while any hypercube is weighted blended, and keeps their targets the size of the view
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_blended_views(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut composite_materials: ResMut<Assets<CompositeMaterial>>,
+    q_hypercubes: Query<&HypercubeStyle, With<Hypercube>>,
+    q_views: Query<
+        (Entity, &Camera, Ref<Projection>, Option<&RenderLayers>),
+        (With<Camera3d>, Without<BlendedCamera>),
+    >,
+    mut q_cameras: Query<&mut Projection, With<BlendedCamera>>,
+    mut q_composites: Query<
+        (
+            Entity,
+            &mut Composite,
+            &Handle<CompositeMaterial>,
+            &mut RenderLayers,
+        ),
+        Without<Camera3d>,
+    >,
+) {
+    let is_used = q_hypercubes.iter().any(is_weighted_blended);
+    let mut composited = HashSet::new();
+    for (entity, mut composite, material_handle, mut layers) in &mut q_composites {
+        let view = q_views.get(composite.view).ok().filter(|_| is_used);
+        let Some((_, camera, projection, view_layers)) = view else {
+            commands.entity(entity).despawn_recursive();
+            for camera in composite.cameras {
+                if let Some(camera) = commands.get_entity(camera) {
+                    camera.despawn_recursive();
+                }
+            }
+            continue;
+        };
+        composited.insert(composite.view);
+        layers.set_if_neq(view_layers.copied().unwrap_or_default());
+        if projection.is_changed() {
+            let mut cameras = q_cameras.iter_many_mut(composite.cameras);
+            while let Some(mut camera_projection) = cameras.fetch_next() {
+                *camera_projection = projection.clone();
+            }
+        }
+        let size = camera.physical_viewport_size().unwrap_or(composite.size);
+        if size != composite.size && size.min_element() > 0 {
+            composite.size = size;
+            let material = composite_materials.get_mut(material_handle).unwrap();
+            for image in [&material.accumulation, &material.revealage] {
+                images.get_mut(image).unwrap().resize(Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    ..default()
+                });
+            }
+        }
+    }
+    if !is_used {
+        return;
+    }
+
+    for (view, camera, projection, view_layers) in &q_views {
+        let size = camera.physical_viewport_size().unwrap_or_default();
+        if composited.contains(&view) || size.min_element() == 0 {
+            continue;
+        }
+        let accumulation = images.add(target_image(size, TextureFormat::Rgba16Float));
+        let revealage = images.add(target_image(size, TextureFormat::bevy_default()));
+        // Only the accumulation pass is HDR, which tells the faces which target they are seen in
+        let passes = [
+            (accumulation.clone(), true, Color::NONE),
+            (revealage.clone(), false, Color::WHITE),
+        ];
+        let cameras = passes.map(|(image, hdr, clear_color)| {
+            commands
+                .spawn((
+                    Camera3dBundle {
+                        camera: Camera {
+                            order: -1,
+                            target: RenderTarget::Image(image),
+                            hdr,
+                            clear_color: ClearColorConfig::Custom(clear_color),
+                            ..default()
+                        },
+                        projection: projection.clone(),
+                        tonemapping: Tonemapping::None,
+                        dither: DebandDither::Disabled,
+                        ..default()
+                    },
+                    blended_layers(view_layers),
+                    BlendedCamera,
+                ))
+                .set_parent(view)
+                .id()
+        });
+        commands.spawn((
+            MaterialMeshBundle {
+                mesh: meshes.add(Rectangle::new(2.0, 2.0)),
+                material: composite_materials.add(CompositeMaterial {
+                    accumulation,
+                    revealage,
+                }),
+                ..default()
+            },
+            view_layers.copied().unwrap_or_default(),
+            NoFrustumCulling,
+            NotShadowCaster,
+            Composite {
+                view,
+                cameras,
+                size,
+            },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hypercube::tests::app;
+    use crate::hypercube::HypercubeBundle;
+    #[test]
+    fn copy_weighted_blended_faces() {
+        let mut app = app(());
+        let mut bundle = HypercubeBundle::new(3);
+        bundle.style.face_blend_mode = FaceBlendMode::WeightedBlended;
+        let entity = app.world.spawn((bundle, RenderLayers::layer(1))).id();
+        app.update();
+        // The faces are only seen through their copies, in the passes of the view of layer 1
+        let mut q_faces = app
+            .world
+            .query_filtered::<(&RenderLayers, &Handle<StandardMaterial>, &Children), With<Face>>();
+        let faces = q_faces
+            .iter(&app.world)
+            .map(|(layers, material_handle, children)| {
+                (*layers, material_handle.clone(), children.to_vec())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(faces.len(), 6);
+        for (layers, _, children) in &faces {
+            assert_eq!(*layers, RenderLayers::none());
+            assert_eq!(children.len(), 1);
+            assert!(app.world.get::<WeightedFace>(children[0]).is_some());
+            assert_eq!(
+                app.world.get::<RenderLayers>(children[0]),
+                Some(&RenderLayers::layer(1 + LAYER_OFFSET))
+            );
+        }
+        let mut q_occluders = app.world.query_filtered::<&Parent, With<Occluder>>();
+        assert_eq!(q_occluders.iter(&app.world).count(), 12);
+
+        // The copies follow the colors of the faces
+        app.world
+            .get_mut::<HypercubeStyle>(entity)
+            .unwrap()
+            .face_color = Color::RED.with_a(0.5);
+        app.update();
+        let (_, material_handle, children) = &faces[0];
+        let color = app
+            .world
+            .resource::<Assets<StandardMaterial>>()
+            .get(material_handle)
+            .unwrap()
+            .base_color;
+        let weighted_handle = app
+            .world
+            .get::<Handle<WeightedFaceMaterial>>(children[0])
+            .unwrap();
+        let weighted_materials = app.world.resource::<Assets<WeightedFaceMaterial>>();
+        assert_eq!(
+            weighted_materials
+                .get(weighted_handle)
+                .unwrap()
+                .base
+                .base_color,
+            color
+        );
+
+        // Blended otherwise, the faces are seen again and the copies are gone
+        app.world
+            .get_mut::<HypercubeStyle>(entity)
+            .unwrap()
+            .face_blend_mode = FaceBlendMode::Sorted;
+        app.update();
+        for (layers, ..) in q_faces.iter(&app.world) {
+            assert_eq!(*layers, RenderLayers::layer(1));
+        }
+        let mut q_copies = app
+            .world
+            .query_filtered::<(), Or<(With<WeightedFace>, With<Occluder>)>>();
+        assert_eq!(q_copies.iter(&app.world).count(), 0);
+    }
+}
@@ -0,0 +1,363 @@
+//! Picking of the vertices, edges and faces of the hypercubes under the cursor, by casting a ray
+//! from the camera against their projected geometry
+
+use crate::hypercube::{
+    Edge, Face, Hypercube, HypercubeHighlight, HypercubeNet, HypercubeProjection, HypercubeStyle,
+};
+use crate::resources::{
+    HoveredElement, InspectedVertices, NCubeCellDimension, ResourcesPlugin, SelectedHypercube,
+};
+use crate::views::ViewHypercube;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::view::RenderLayers;
+use bevy::window::PrimaryWindow;
+use ncube_core::{walk, NCell};
+
+pub struct PickingPlugin;
+
+impl Plugin for PickingPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ResourcesPlugin>() {
+            app.add_plugins(ResourcesPlugin);
+        }
+        app.configure_sets(
+            Update,
+            (PickingSystems::Hover, PickingSystems::Select).chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                hover_elements.in_set(PickingSystems::Hover),
+                select_hovered_element.in_set(PickingSystems::Select),
+            ),
+        );
+    }
+}
+
+/// Systems that find the element under the cursor, then select it when clicked. Those in
+/// between can clear the [`HoveredElement`], e.g. when the cursor is over a window of the UI.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PickingSystems {
+    Hover,
+    Select,
+}
+
+/// Element of a hypercube, by index in its vertices, edges or faces
+#[derive(Debug, Clone, PartialEq)]
+pub enum HypercubeElement {
+    Vertex(usize),
+    Edge(usize),
+    Face(usize),
+}
+
+impl HypercubeElement {
+    /// Indices of the vertices of the element in `ncube`
+    pub fn vertices(&self, ncube: &ncube_core::NCube) -> Vec<usize> {
+        match self {
+            Self::Vertex(i) => vec![*i],
+            Self::Edge(e) => {
+                let (i, j) = ncube.edges.0[*e];
+                vec![i, j]
+            }
+            Self::Face(k) => ncube.faces.0[*k].clone(),
+        }
+    }
+
+    /// Describes the element of `ncube`: the n-D coordinates of a vertex or edge, the vertex
+    /// indices of a face
+    pub fn describe(&self, ncube: &ncube_core::NCube) -> String {
+        let n = ncube.dimensions;
+        let vertex = |i: usize| {
+            let coordinates = ncube.vertices.0[i]
+                .iter()
+                .map(|x| format!("{x:+.2}"))
+                .collect::<Vec<_>>();
+            format!(
+                "{i} ({}): [{}]",
+                walk::bit_string(i, n),
+                coordinates.join(", ")
+            )
+        };
+        let vertices = self.vertices(ncube);
+        match self {
+            Self::Vertex(_) => format!("vertex {}", vertex(vertices[0])),
+            Self::Edge(e) => format!(
+                "edge {e}\n{}",
+                vertices
+                    .into_iter()
+                    .map(vertex)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            Self::Face(k) => {
+                let indices = vertices.iter().map(usize::to_string).collect::<Vec<_>>();
+                format!("face {k}\nvertices {}", indices.join(", "))
+            }
+        }
+    }
+}
+
+/// Element of a hypercube entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickedElement {
+    pub hypercube: Entity,
+    pub element: HypercubeElement,
+}
+
+/// Vertices and edges are picked within this many edge thicknesses of them
+const PICK_TOLERANCE: f32 = 3.0;
+
+/// Distance along `direction` from `origin` to where the ray enters the box centered on the
+/// origin with the given half extents
+fn ray_box(origin: Vec3, direction: Vec3, half_extents: Vec3) -> Option<f32> {
+    let inverse = direction.recip();
+    let t1 = (-half_extents - origin) * inverse;
+    let t2 = (half_extents - origin) * inverse;
+    let t_near = t1.min(t2).max_element();
+    let t_far = t1.max(t2).min_element();
+    (t_near <= t_far && t_far >= 0.0).then_some(t_near.max(0.0))
+}
+
+/// Distance along `direction` from `origin` to the triangle `(a, b, c)` (Möller and Trumbore,
+/// 1997)
+fn ray_triangle(origin: Vec3, direction: Vec3, [a, b, c]: [Vec3; 3]) -> Option<f32> {
+    let (ab, ac) = (b - a, c - a);
+    let p = direction.cross(ac);
+    let det = ab.dot(p);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+    let ao = origin - a;
+    let u = ao.dot(p) / det;
+    let q = ao.cross(ab);
+    let v = direction.dot(q) / det;
+    let t = ac.dot(q) / det;
+    (u >= 0.0 && v >= 0.0 && u + v <= 1.0 && t >= 0.0).then_some(t)
+}
+
+/// Distance along `direction`, of unit length, from `origin` to the sphere
+fn ray_sphere(origin: Vec3, direction: Vec3, center: Vec3, radius: f32) -> Option<f32> {
+    let oc = origin - center;
+    let b = oc.dot(direction);
+    let discriminant = b * b - (oc.length_squared() - radius * radius);
+    let t = -b - discriminant.sqrt();
+    (discriminant >= 0.0 && t >= 0.0).then_some(t)
+}
+
+/// Nearest element hit by `ray`, preferring vertices to edges and edges to faces since they are
+/// drawn over them
+fn pick(
+    ray: Ray3d,
+    hypercube: &Hypercube,
+    transform: &GlobalTransform,
+    (style, net): (&HypercubeStyle, Option<&HypercubeNet>),
+    edges: &[(&Edge, &GlobalTransform)],
+    faces: &[&Face],
+) -> Option<(f32, HypercubeElement)> {
+    // The local spaces keep the distances along the ray comparable, as they are affine
+    let to_local = |transform: &GlobalTransform| {
+        let inverse = transform.affine().inverse();
+        (
+            inverse.transform_point3(ray.origin),
+            inverse.transform_vector3(*ray.direction),
+        )
+    };
+    let nearest = |hits: Vec<(f32, HypercubeElement)>| {
+        hits.into_iter().min_by(|(t1, _), (t2, _)| t1.total_cmp(t2))
+    };
+    let (origin, direction) = to_local(transform);
+    let radius = PICK_TOLERANCE * style.edge_thickness;
+    let vertices = hypercube
+        .vertices_3d
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| {
+            let t = ray_sphere(origin, direction.normalize(), *v, radius)? / direction.length();
+            Some((t, HypercubeElement::Vertex(HypercubeNet::origin(net, i))))
+        });
+    let edges = edges.iter().filter_map(|(edge, transform)| {
+        let (origin, direction) = to_local(transform);
+        let half_extents = Vec3::new(PICK_TOLERANCE / 2.0, PICK_TOLERANCE / 2.0, 0.5);
+        let t = ray_box(origin, direction, half_extents)?;
+        Some((t, HypercubeElement::Edge(edge.index)))
+    });
+    let faces = faces.iter().filter_map(|face| {
+        let v = |k: usize| hypercube.vertices_3d[face.vertices[k]];
+        let t = (1..face.vertices.len() - 1)
+            .filter_map(|k| ray_triangle(origin, direction, [v(0), v(k), v(k + 1)]))
+            .min_by(f32::total_cmp)?;
+        Some((t, HypercubeElement::Face(face.index)))
+    });
+    nearest(vertices.collect())
+        .or_else(|| nearest(edges.collect()))
+        .or_else(|| nearest(faces.collect()))
+}
+
+/// Finds the element under the cursor, in the view of the camera it is over
+#[allow(clippy::type_complexity)]
+fn hover_elements(
+    mut hovered_element: ResMut<HoveredElement>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_cameras: Query<(&Camera, &GlobalTransform, Option<&RenderLayers>)>,
+    q_hypercubes: Query<(
+        Entity,
+        &Hypercube,
+        &GlobalTransform,
+        &HypercubeStyle,
+        Option<&HypercubeNet>,
+        Option<&RenderLayers>,
+        &InheritedVisibility,
+        &Children,
+        (Option<&HypercubeProjection>, Option<&ViewHypercube>),
+    )>,
+    q_edges: Query<(&Edge, &GlobalTransform)>,
+    q_faces: Query<(&Face, &InheritedVisibility)>,
+) {
+    let cursor = q_window
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let camera = cursor.and_then(|cursor| {
+        q_cameras
+            .iter()
+            // Not those of the weighted blended passes, which render to images
+            .filter(|(camera, ..)| {
+                camera.is_active && matches!(camera.target, RenderTarget::Window(_))
+            })
+            .filter_map(|(camera, transform, layers)| {
+                let viewport = camera.logical_viewport_rect()?;
+                viewport.contains(cursor).then_some((
+                    camera,
+                    transform,
+                    layers,
+                    cursor - viewport.min,
+                ))
+            })
+            .max_by_key(|(camera, ..)| camera.order)
+    });
+    let Some((camera, camera_transform, camera_layers, position)) = camera else {
+        if hovered_element.is_some() {
+            **hovered_element = None;
+        }
+        return;
+    };
+    let ray = camera.viewport_to_world(camera_transform, position);
+    let camera_layers = camera_layers.copied().unwrap_or_default();
+
+    let picked = ray.and_then(|ray| {
+        q_hypercubes
+            .iter()
+            .filter(|(.., layers, visibility, _, (projection, _))| {
+                // Nothing but the cross-section is shown of a cut hypercube
+                visibility.get()
+                    && projection.copied().unwrap_or_default() != HypercubeProjection::CrossSection
+                    && layers
+                        .copied()
+                        .unwrap_or_default()
+                        .intersects(&camera_layers)
+            })
+            .filter_map(
+                |(entity, hypercube, transform, style, net, _, _, children, (_, copy))| {
+                    let edges = q_edges.iter_many(children).collect::<Vec<_>>();
+                    let faces = q_faces
+                        .iter_many(children)
+                        .filter(|(_, visibility)| visibility.get())
+                        .map(|(face, _)| face)
+                        .collect::<Vec<_>>();
+                    let (t, element) =
+                        pick(ray, hypercube, transform, (style, net), &edges, &faces)?;
+                    Some((
+                        t,
+                        PickedElement {
+                            hypercube: copy.map_or(entity, |copy| copy.source),
+                            element,
+                        },
+                    ))
+                },
+            )
+            .min_by(|(t1, _), (t2, _)| t1.total_cmp(t2))
+            .map(|(_, picked)| picked)
+    });
+    if **hovered_element != picked {
+        **hovered_element = picked;
+    }
+}
+
+/// Selects the hypercube clicked and highlights the cell made of the element clicked, and
+/// inspects the vertex clicked along with the one before it
+fn select_hovered_element(
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    hovered_element: Res<HoveredElement>,
+    mut selected_hypercube: ResMut<SelectedHypercube>,
+    mut ncube_cell_dimension: ResMut<NCubeCellDimension>,
+    mut inspected_vertices: ResMut<InspectedVertices>,
+    mut q_hypercubes: Query<(&Hypercube, &mut HypercubeHighlight)>,
+) {
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(picked) = &**hovered_element else {
+        return;
+    };
+    let Ok((hypercube, mut highlight)) = q_hypercubes.get_mut(picked.hypercube) else {
+        return;
+    };
+    let n = hypercube.ncube.dimensions;
+    let cell = NCell::spanned(&picked.element.vertices(&hypercube.ncube), n);
+    let m = n - cell.fixed.len();
+    highlight.cell = (highlight.cell.as_ref() != Some(&cell)).then_some(cell);
+    if **selected_hypercube != Some(picked.hypercube) {
+        **selected_hypercube = Some(picked.hypercube);
+    }
+    if m > 0 {
+        **ncube_cell_dimension = m;
+    }
+    if let HypercubeElement::Vertex(i) = picked.element {
+        **inspected_vertices = [inspected_vertices[1], i];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn ray_intersections() {
+        let (origin, direction) = (Vec3::new(0.0, 0.0, 5.0), Vec3::NEG_Z);
+        assert_eq!(ray_box(origin, direction, Vec3::splat(0.5)), Some(4.5));
+        assert_eq!(ray_box(origin, Vec3::Z, Vec3::splat(0.5)), None);
+        let triangle = [
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(1.0, -1.0, 1.0),
+            Vec3::new(0.0, 1.0, 1.0),
+        ];
+        assert_eq!(ray_triangle(origin, direction, triangle), Some(4.0));
+        let offset = Vec3::new(2.0, 0.0, 0.0);
+        assert_eq!(ray_triangle(origin + offset, direction, triangle), None);
+        assert_eq!(ray_sphere(origin, direction, Vec3::ZERO, 1.0), Some(4.0));
+    }
+    #[test]
+    fn pick_vertex() {
+        let hypercube = Hypercube::new(3, 1.0);
+        // Vertex 1 is in front of vertex 5, which only differs from it along the z axis
+        let vertex = hypercube.vertices_3d[5];
+        let ray = Ray3d::new(vertex + Vec3::new(0.0, 0.0, 5.0), Vec3::NEG_Z);
+        let style = HypercubeStyle::default();
+        let (_, element) = pick(
+            ray,
+            &hypercube,
+            &GlobalTransform::IDENTITY,
+            (&style, None),
+            &[],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(element, HypercubeElement::Vertex(1));
+        assert_eq!(
+            NCell::spanned(&element.vertices(&hypercube.ncube), 3)
+                .fixed
+                .len(),
+            3
+        );
+    }
+}
@@ -1,31 +1,52 @@
-use crate::ncube::{self, ExtendedMathOps};
+use crate::hypercube::{Hypercube, HypercubeProjection};
+use crate::picking::PickedElement;
+use crate::views::{ViewHypercube, MAX_VIEWS};
 use bevy::prelude::*;
-use std::collections::HashMap;
 
 pub struct ResourcesPlugin;
 
 impl Plugin for ResourcesPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<NCubeDimension>()
-            .init_resource::<NCube>()
-            .init_resource::<NCubeVertices3D>()
-            .init_resource::<NCubePlanesOfRotation>()
-            .init_resource::<NCubeRotations>()
-            .init_resource::<NCubeIsPaused>()
-            .init_resource::<NCubeEdgeColor>()
-            .init_resource::<NCubeFaceColor>()
-            .init_resource::<NCubeEdgeThickness>()
-            .init_resource::<NCubeUnlit>()
+        app.init_resource::<NCubeCellDimension>()
             .init_resource::<IsHoveringFile>()
             .init_resource::<FileDialog>()
+            .init_resource::<OpenFileDialog>()
+            .init_resource::<RecentFiles>()
+            .init_resource::<ErrorToasts>()
             .init_resource::<ShowControls>()
+            .init_resource::<ShowGallery>()
+            .init_resource::<GalleryPresets>()
+            .init_resource::<ShowUi>()
+            .init_resource::<ShowInfoText>()
+            .init_resource::<LoadPaused>()
+            .init_resource::<PendingNCubeData>()
+            .init_resource::<PendingDataExport>()
             .init_resource::<FontHandle>()
-            .init_resource::<OrthographicCamera>();
+            .init_resource::<OrthographicCamera>()
+            .init_resource::<DragRotation>()
+            .init_resource::<DragPlanes>()
+            .init_resource::<DragMomentum>()
+            .init_resource::<SplitScreenViews>()
+            .init_resource::<SplitScreenProjections>()
+            .init_resource::<InspectedVertices>()
+            .init_resource::<HoveredElement>()
+            .init_resource::<SceneBackground>()
+            .init_resource::<SceneLights>()
+            .init_resource::<SceneAmbientLight>()
+            .init_resource::<SceneShowGrid>()
+            .init_resource::<SceneShowAxes>()
+            .init_resource::<HypercubeOrder>()
+            .init_resource::<SelectedHypercube>()
+            .add_systems(PreUpdate, (order_hypercubes, select_hypercube).chain());
     }
 }
 
 pub const SIZE: f32 = 1.0;
 
+pub const MIN_DIMENSION: usize = 3;
+pub const MAX_DIMENSION: usize = 9;
+pub const DEFAULT_DIMENSION: usize = 5;
+
 #[macro_export]
 macro_rules! impl_default {
     ($s:ident => $i:expr) => {
@@ -49,62 +70,267 @@ macro_rules! create_resource {
     };
 }
 
-create_resource!(NCubeDimension(usize) => Self(5));
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum FaceBlendMode {
+    /// Adds the faces' colors together, which only looks right over dark backgrounds
+    Additive,
+    /// Alpha blends the faces back to front, sorted by their distance to the camera
+    Sorted,
+    /// Blends the faces in any order with weighted blended order-independent transparency,
+    /// which weighs each face by its distance to the camera rather than sorting them
+    #[serde(alias = "weighted", alias = "depth_weighted")]
+    WeightedBlended,
+}
+impl_default!(FaceBlendMode => Self::Additive);
+
+create_resource!(
+    /// Dimension of the cells listed in the cell browser
+    NCubeCellDimension(usize) => Self(3)
+);
 
-create_resource!(NCube(ncube::NCube) => {
-    let d = NCubeDimension::default();
-    Self(ncube::NCube::new(*d, SIZE.into()))
-});
+create_resource!(IsHoveringFile(bool) => Self(false));
 
-create_resource!(NCubeVertices3D(Vec<Vec3>) => {
-    let ncube = NCube::default();
-    Self(ncube.perspective_project_vertices())
-});
+#[cfg(not(target_family = "wasm"))]
+create_resource!(FileDialog(Option<egui_file::FileDialog>) => Self(None));
+#[cfg(target_family = "wasm")]
+create_resource!(FileDialog(()) => Self(()));
 
+#[cfg(not(target_family = "wasm"))]
+create_resource!(OpenFileDialog(Option<egui_file::FileDialog>) => Self(None));
+#[cfg(target_family = "wasm")]
+create_resource!(OpenFileDialog(()) => Self(()));
+
+#[cfg(not(target_family = "wasm"))]
 create_resource!(
-    /// Dimension indices
-    NCubePlanesOfRotation(Vec<(usize, usize)>) => {
-        let d = NCubeDimension::default();
-        Self(usize::pair_permutations(0, *d - 1))
-    }
+    /// Most recently loaded data files, persisted across sessions
+    RecentFiles(Vec<std::path::PathBuf>) => Self(crate::config::load_recent_files())
 );
+#[cfg(target_family = "wasm")]
+create_resource!(RecentFiles(()) => Self(()));
 
 create_resource!(
-    /// k: Plane
-    /// v: Angle, angular velocity
-    NCubeRotations(
-        std::collections::HashMap<(usize, usize), (f64, f64)>
-    ) => {
-        let planes_of_rotation = NCubePlanesOfRotation::default();
-        let mut rotations: HashMap<(usize, usize), (f64, f64)> = HashMap::new();
-        for plane in &*planes_of_rotation {
-            rotations.insert(*plane, (0.0, 0.0));
-        }
-        rotations.insert((1, 2), (0.0, 1.0));
-        rotations.insert((0, 3), (0.0, 0.5));
-        Self(rotations)
-    }
+    /// Error messages shown until the user dismisses them
+    ErrorToasts(Vec<String>) => Self(Vec::new())
 );
 
-create_resource!(NCubeIsPaused(bool) => Self(false));
+create_resource!(ShowControls(bool) => Self(false));
+
+create_resource!(ShowGallery(bool) => Self(false));
 
-create_resource!(NCubeEdgeColor(Color) => Self(Color::CYAN));
+create_resource!(
+    /// Presets bundled with the app
+    GalleryPresets(Vec<crate::data::NCubeData>) => Self(crate::gallery::presets())
+);
 
-create_resource!(NCubeFaceColor(Color) => Self(Color::CYAN.with_a(0.1)));
+create_resource!(
+    /// Whether the settings and controls windows are shown
+    ShowUi(bool) => Self(true)
+);
 
-create_resource!(NCubeEdgeThickness(f32) => Self(0.01 * SIZE));
+create_resource!(
+    /// Whether the angle of each plane of rotation is shown
+    ShowInfoText(bool) => Self(true)
+);
 
-create_resource!(NCubeUnlit(bool) => Self(false));
+create_resource!(
+    /// Data file contents that replace the scene on the next update
+    PendingNCubeData(Option<crate::data::NCubeData>) => Self(None)
+);
 
-create_resource!(IsHoveringFile(bool) => Self(false));
+/// Where the scene is exported to
+pub enum DataExport {
+    #[cfg(not(target_family = "wasm"))]
+    File(std::path::PathBuf),
+    #[cfg(target_family = "wasm")]
+    Download,
+    #[cfg(target_family = "wasm")]
+    ShareLink,
+}
 
-#[cfg(not(target_family = "wasm"))]
-create_resource!(FileDialog(Option<egui_file::FileDialog>) => Self(None));
-#[cfg(target_family = "wasm")]
-create_resource!(FileDialog(()) => Self(()));
+create_resource!(
+    /// Export of the scene requested for the next update
+    PendingDataExport(Option<DataExport>) => Self(None)
+);
 
-create_resource!(ShowControls(bool) => Self(false));
+create_resource!(
+    /// Whether data files are loaded paused, regardless of their saved state
+    LoadPaused(bool) => Self(false)
+);
 
 create_resource!(FontHandle(Handle<Font>) => Self(Handle::default()));
 
 create_resource!(OrthographicCamera(bool) => Self(false));
+
+create_resource!(
+    /// Whether dragging with the right mouse button turns the selected hypercube instead of
+    /// orbiting the camera
+    DragRotation(bool) => Self(false)
+);
+
+create_resource!(
+    /// Planes that horizontal and vertical drags turn the hypercube in, before the modifier keys
+    /// raise their second dimension
+    DragPlanes([(usize, usize); 2]) => Self([(0, 3), (1, 3)])
+);
+
+create_resource!(
+    /// Whether the planes keep turning at the speed they were dragged at once released
+    DragMomentum(bool) => Self(false)
+);
+
+create_resource!(
+    /// Number of views the window is split into, each showing one hypercube. Once every
+    /// hypercube has a view, the next views show them again.
+    SplitScreenViews(usize) => Self(1)
+);
+
+create_resource!(
+    /// How the hypercube of each view of a split screen is brought down into 3D
+    SplitScreenProjections([HypercubeProjection; MAX_VIEWS]) =>
+        Self([HypercubeProjection::Perspective; MAX_VIEWS])
+);
+
+create_resource!(
+    /// Vertices of the selected hypercube compared in the skeleton inspector, the last one
+    /// picked second
+    InspectedVertices([usize; 2]) => Self([0, 0])
+);
+
+create_resource!(
+    /// Element of a hypercube under the cursor
+    HoveredElement(Option<PickedElement>) => Self(None)
+);
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Background {
+    Color(#[schemars(with = "crate::data::ColorSchema")] Color),
+    /// Vertical gradient from the top color to the bottom color
+    Gradient(
+        #[schemars(with = "crate::data::ColorSchema")] Color,
+        #[schemars(with = "crate::data::ColorSchema")] Color,
+    ),
+}
+impl_default!(Background => Self::Color(Color::BLACK));
+
+create_resource!(SceneBackground(Background) => Self(Background::default()));
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub struct SceneLight {
+    #[schemars(with = "[f32; 3]")]
+    pub position: Vec3,
+    #[schemars(with = "crate::data::ColorSchema")]
+    pub color: Color,
+    pub intensity: f32,
+}
+
+pub fn default_scene_lights() -> Vec<SceneLight> {
+    let intensity = 1500000.0 * SIZE.powi(2);
+    vec![
+        SceneLight {
+            position: Vec3::new(3.0, 8.0, 4.0) * SIZE,
+            color: Color::WHITE,
+            intensity,
+        },
+        SceneLight {
+            position: Vec3::new(-4.0, 8.0, -4.0) * SIZE,
+            color: Color::WHITE,
+            intensity,
+        },
+    ]
+}
+
+create_resource!(
+    /// Point lights of the scene
+    SceneLights(Vec<SceneLight>) => Self(default_scene_lights())
+);
+
+pub const DEFAULT_AMBIENT_LIGHT: f32 = 80.0;
+
+create_resource!(SceneAmbientLight(f32) => Self(DEFAULT_AMBIENT_LIGHT));
+
+create_resource!(SceneShowGrid(bool) => Self(false));
+
+create_resource!(SceneShowAxes(bool) => Self(false));
+
+create_resource!(
+    /// Hypercubes in the order they are listed, shown in the views and saved in. Those spawned
+    /// since the last update are added at the end.
+    HypercubeOrder(Vec<Entity>) => Self(Vec::new())
+);
+
+create_resource!(
+    /// Hypercube that the settings, overlay text and undo history work on. The first hypercube
+    /// is selected when there is none.
+    SelectedHypercube(Option<Entity>) => Self(None)
+);
+
+fn order_hypercubes(
+    mut hypercube_order: ResMut<HypercubeOrder>,
+    q_hypercubes: Query<Entity, (With<Hypercube>, Without<ViewHypercube>)>,
+) {
+    let mut spawned: Vec<Entity> = q_hypercubes
+        .iter()
+        .filter(|entity| !hypercube_order.contains(entity))
+        .collect();
+    let is_despawned = hypercube_order
+        .iter()
+        .any(|entity| !q_hypercubes.contains(*entity));
+    if spawned.is_empty() && !is_despawned {
+        return;
+    }
+    spawned.sort();
+    hypercube_order.retain(|entity| q_hypercubes.contains(*entity));
+    hypercube_order.extend(spawned);
+}
+
+fn select_hypercube(
+    hypercube_order: Res<HypercubeOrder>,
+    mut selected_hypercube: ResMut<SelectedHypercube>,
+    q_hypercubes: Query<(), (With<Hypercube>, Without<ViewHypercube>)>,
+) {
+    if !selected_hypercube.is_some_and(|entity| q_hypercubes.contains(entity)) {
+        **selected_hypercube = hypercube_order.first().copied();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hypercube::tests::app;
+    use crate::hypercube::HypercubeBundle;
+    #[test]
+    fn order_and_select_hypercubes() {
+        let mut app = app(ResourcesPlugin);
+        let first = app.world.spawn(HypercubeBundle::new(3)).id();
+        let second = app.world.spawn(HypercubeBundle::new(5)).id();
+        app.update();
+        assert_eq!(
+            **app.world.resource::<HypercubeOrder>(),
+            vec![first, second]
+        );
+        assert_eq!(**app.world.resource::<SelectedHypercube>(), Some(first));
+
+        // The order given to hypercubes is kept, whatever their entities
+        **app.world.resource_mut::<HypercubeOrder>() = vec![second, first];
+        **app.world.resource_mut::<SelectedHypercube>() = Some(second);
+        let third = app.world.spawn(HypercubeBundle::new(4)).id();
+        app.update();
+        assert_eq!(
+            **app.world.resource::<HypercubeOrder>(),
+            vec![second, first, third]
+        );
+
+        app.world.despawn(second);
+        app.update();
+        assert_eq!(**app.world.resource::<HypercubeOrder>(), vec![first, third]);
+        assert_eq!(**app.world.resource::<SelectedHypercube>(), Some(first));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::camera::MainCamera;
+use crate::resources::{
+    default_scene_lights, Background, FaceBlendMode, SceneAmbientLight, SceneBackground,
+    SceneLight, SceneLights, SceneShowAxes, SceneShowGrid, DEFAULT_AMBIENT_LIGHT, SIZE,
+};
+use crate::views::view_layers;
+use bevy::prelude::*;
+use bevy::render::camera::ClearColorConfig;
+
+pub struct ScenePlugin;
+
+impl Plugin for ScenePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (spawn_skydome, configure_gizmos))
+            .add_systems(
+                Update,
+                (
+                    update_background,
+                    update_lights,
+                    update_ambient_light,
+                    draw_gizmos,
+                ),
+            );
+    }
+}
+
+const SKYDOME_RADIUS: f32 = 100.0 * SIZE;
+const GRID_HEIGHT: f32 = -1.5 * SIZE;
+const GRID_SPACING: f32 = 0.5 * SIZE;
+const GRID_LINES: i32 = 20;
+const AXES_LENGTH: f32 = 1.5 * SIZE;
+
+#[derive(Component)]
+struct Skydome;
+#[derive(Component)]
+struct Light;
+
+/// A named set of scene and style settings
+pub struct ScenePreset {
+    pub name: &'static str,
+    pub background: Background,
+    pub lights: Vec<SceneLight>,
+    pub ambient_light: f32,
+    pub edge_color: Color,
+    pub face_color: Color,
+    pub face_blend_mode: FaceBlendMode,
+}
+
+pub fn scene_presets() -> Vec<ScenePreset> {
+    vec![
+        ScenePreset {
+            name: "default",
+            background: Background::default(),
+            lights: default_scene_lights(),
+            ambient_light: DEFAULT_AMBIENT_LIGHT,
+            edge_color: Color::CYAN,
+            face_color: Color::CYAN.with_a(0.1),
+            face_blend_mode: FaceBlendMode::Additive,
+        },
+        // Meant for print figures, additive blending would vanish over white
+        ScenePreset {
+            name: "paper white",
+            background: Background::Color(Color::WHITE),
+            lights: default_scene_lights(),
+            ambient_light: 4.0 * DEFAULT_AMBIENT_LIGHT,
+            edge_color: Color::BLACK,
+            face_color: Color::GRAY.with_a(0.15),
+            face_blend_mode: FaceBlendMode::Sorted,
+        },
+        ScenePreset {
+            name: "night sky",
+            background: Background::Gradient(Color::MIDNIGHT_BLUE, Color::BLACK),
+            lights: vec![SceneLight {
+                position: Vec3::new(0.0, 8.0, 0.0) * SIZE,
+                color: Color::ALICE_BLUE,
+                intensity: 2000000.0 * SIZE.powi(2),
+            }],
+            ambient_light: DEFAULT_AMBIENT_LIGHT,
+            edge_color: Color::GOLD,
+            face_color: Color::ORANGE.with_a(0.1),
+            face_blend_mode: FaceBlendMode::Additive,
+        },
+    ]
+}
+
+/// Spawns a sphere around the scene that shows the background gradient
+fn spawn_skydome(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        MaterialMeshBundle {
+            mesh: meshes.add(Sphere::new(SKYDOME_RADIUS).mesh().uv(32, 18)),
+            material: materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                unlit: true,
+                cull_mode: None,
+                ..default()
+            }),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Skydome,
+        // Seen from every view of a split screen
+        view_layers(),
+    ));
+}
+
+fn configure_gizmos(mut config_store: ResMut<GizmoConfigStore>) {
+    let (config, _) = config_store.config_mut::<DefaultGizmoConfigGroup>();
+    config.render_layers = view_layers();
+}
+
+fn update_background(
+    scene_background: Res<SceneBackground>,
+    mut q_camera: Query<&mut Camera, With<MainCamera>>,
+    mut q_skydome: Query<(&Handle<Mesh>, &mut Visibility), With<Skydome>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if !scene_background.is_changed() {
+        return;
+    }
+    let mut camera = q_camera.get_single_mut().unwrap();
+    let (mesh_handle, mut visibility) = q_skydome.get_single_mut().unwrap();
+    match **scene_background {
+        Background::Color(color) => {
+            camera.clear_color = ClearColorConfig::Custom(color);
+            *visibility = Visibility::Hidden;
+        }
+        Background::Gradient(top, bottom) => {
+            camera.clear_color = ClearColorConfig::Custom(bottom);
+            *visibility = Visibility::Inherited;
+            let (top, bottom) = (top.as_linear_rgba_f32(), bottom.as_linear_rgba_f32());
+            let mesh = meshes.get_mut(mesh_handle).unwrap();
+            let colors = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+                Some(positions) => positions
+                    .as_float3()
+                    .unwrap()
+                    .iter()
+                    .map(|p| {
+                        let t = (p[1] / SKYDOME_RADIUS + 1.0) / 2.0;
+                        [0, 1, 2, 3].map(|c| bottom[c] * (1.0 - t) + top[c] * t)
+                    })
+                    .collect::<Vec<_>>(),
+                None => return,
+            };
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
+    }
+}
+
+fn update_lights(
+    mut commands: Commands,
+    scene_lights: Res<SceneLights>,
+    q_lights: Query<Entity, With<Light>>,
+) {
+    if !scene_lights.is_changed() {
+        return;
+    }
+    q_lights.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+    for light in scene_lights.iter() {
+        commands.spawn((
+            PointLightBundle {
+                point_light: PointLight {
+                    color: light.color,
+                    intensity: light.intensity,
+                    range: 20.0 * SIZE,
+                    ..default()
+                },
+                transform: Transform::from_translation(light.position),
+                ..default()
+            },
+            Light,
+        ));
+    }
+}
+
+fn update_ambient_light(
+    scene_ambient_light: Res<SceneAmbientLight>,
+    mut ambient_light: ResMut<AmbientLight>,
+) {
+    if scene_ambient_light.is_changed() {
+        ambient_light.brightness = **scene_ambient_light;
+    }
+}
+
+fn draw_gizmos(
+    mut gizmos: Gizmos,
+    scene_show_grid: Res<SceneShowGrid>,
+    scene_show_axes: Res<SceneShowAxes>,
+) {
+    if **scene_show_grid {
+        let half = GRID_LINES as f32 * GRID_SPACING / 2.0;
+        for i in 0..=GRID_LINES {
+            let offset = i as f32 * GRID_SPACING - half;
+            gizmos.line(
+                Vec3::new(offset, GRID_HEIGHT, -half),
+                Vec3::new(offset, GRID_HEIGHT, half),
+                Color::GRAY,
+            );
+            gizmos.line(
+                Vec3::new(-half, GRID_HEIGHT, offset),
+                Vec3::new(half, GRID_HEIGHT, offset),
+                Color::GRAY,
+            );
+        }
+    }
+    if **scene_show_axes {
+        gizmos.arrow(Vec3::ZERO, Vec3::X * AXES_LENGTH, Color::RED);
+        gizmos.arrow(Vec3::ZERO, Vec3::Y * AXES_LENGTH, Color::GREEN);
+        gizmos.arrow(Vec3::ZERO, Vec3::Z * AXES_LENGTH, Color::BLUE);
+    }
+}
@@ -0,0 +1,76 @@
+//! Last session, saved periodically and on exit and restored on startup
+
+use crate::data::{NCubeData, NCubeState};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_family = "wasm")]
+#[wasm_bindgen]
+extern "C" {
+    fn load_session() -> Option<String>;
+    fn save_session(data: String);
+}
+
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, autosave_session);
+    }
+}
+
+/// Seconds between saves of the session
+const SAVE_INTERVAL: f32 = 5.0;
+
+#[cfg(not(target_family = "wasm"))]
+fn session_path() -> Option<std::path::PathBuf> {
+    crate::config::config_dir().map(|dir| dir.join("session.data"))
+}
+
+/// Last saved session, if there is a valid one
+pub fn load() -> Option<NCubeData> {
+    #[cfg(not(target_family = "wasm"))]
+    return NCubeData::read(&session_path()?).ok();
+    #[cfg(target_family = "wasm")]
+    return NCubeData::parse(&load_session()?).ok();
+}
+
+fn save(data: String) {
+    #[cfg(not(target_family = "wasm"))]
+    {
+        let Some(path) = session_path() else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).unwrap_or(());
+        }
+        std::fs::write(path, data).unwrap_or(());
+    }
+    #[cfg(target_family = "wasm")]
+    save_session(data);
+}
+
+fn autosave_session(
+    time: Res<Time>,
+    mut app_exit: EventReader<AppExit>,
+    ncube_state: NCubeState,
+    mut last_save: Local<(f32, String)>,
+) {
+    let is_exiting = app_exit.read().next().is_some();
+    if time.elapsed_seconds() - last_save.0 < SAVE_INTERVAL && !is_exiting
+        || ncube_state.is_rebuilding()
+    {
+        return;
+    }
+    last_save.0 = time.elapsed_seconds();
+    let Ok(data) = serde_json::to_string_pretty(&ncube_state.snapshot()) else {
+        return;
+    };
+    if data != last_save.1 {
+        save(data.clone());
+        last_save.1 = data;
+    }
+}
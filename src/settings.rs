@@ -1,110 +1,195 @@
-use crate::camera::{get_default_camera_projection, get_default_camera_transform};
-use crate::impl_default;
-use crate::ncube::NCube as InnerNCube;
-use crate::resources::{FileDialog, IsHoveringFile, ShowControls, SIZE};
-use crate::NCube;
-use crate::NCubeDimension;
-use crate::NCubeEdgeColor;
-use crate::NCubeEdgeThickness;
-use crate::NCubeFaceColor;
-use crate::NCubeIsPaused;
-use crate::NCubePlanesOfRotation;
-use crate::NCubeRotations;
-use crate::NCubeUnlit;
-use crate::NCubeVertices3D;
+use crate::camera::{get_default_camera_projection, MainCamera};
+use crate::data::{DataPlugin, HypercubeData, NCubeData, NCubeState};
+use crate::hypercube::{
+    face_plane_color, HypercubeComponents, HypercubeComponentsItem, HypercubeDimension,
+    HypercubeExtrusion, HypercubeHighlight, HypercubeProjection, HypercubeRotations,
+    HypercubeStyle, HypercubeUnfolding, HypercubeWalk, WalkPath,
+};
+use crate::picking::{PickingPlugin, PickingSystems};
+use crate::resources::{
+    default_scene_lights, Background, DataExport, DragMomentum, DragPlanes, DragRotation,
+    ErrorToasts, FaceBlendMode, FileDialog, GalleryPresets, HoveredElement, HypercubeOrder,
+    InspectedVertices, IsHoveringFile, LoadPaused, NCubeCellDimension, OpenFileDialog,
+    PendingDataExport, PendingNCubeData, RecentFiles, ResourcesPlugin, SceneAmbientLight,
+    SceneBackground, SceneLights, SceneShowAxes, SceneShowGrid, SelectedHypercube, ShowControls,
+    ShowGallery, ShowInfoText, ShowUi, SplitScreenProjections, SplitScreenViews, DEFAULT_DIMENSION,
+    MAX_DIMENSION, MIN_DIMENSION, SIZE,
+};
+use crate::scene::scene_presets;
+use crate::views::{ArrangedHypercube, ViewCamera, ViewsPlugin, MAX_VIEWS};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
 use egui::Ui;
+use ncube_core::walk;
+use ncube_core::ExtendedMathOps;
+use ncube_core::NCell as InnerNCell;
+use ncube_core::NSkeleton;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
 extern "C" {
     fn get_drag_drop_data() -> Option<String>;
     fn export_to_data_file(dimension: usize, data: String);
+    fn get_share_state() -> Option<String>;
+    fn copy_share_link(state: String);
 }
 
 pub struct SettingsPlugin;
 
 impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(EguiPlugin)
-            .add_systems(Update, (info_panel, controls_panel));
+        if !app.is_plugin_added::<ResourcesPlugin>() {
+            app.add_plugins(ResourcesPlugin);
+        }
+        if !app.is_plugin_added::<DataPlugin>() {
+            app.add_plugins(DataPlugin);
+        }
+        if !app.is_plugin_added::<ViewsPlugin>() {
+            app.add_plugins(ViewsPlugin);
+        }
+        if !app.is_plugin_added::<PickingPlugin>() {
+            app.add_plugins(PickingPlugin);
+        }
+        app.add_plugins(EguiPlugin).add_systems(
+            Update,
+            (
+                (info_panel, controls_panel, gallery_panel)
+                    .run_if(|show_ui: Res<ShowUi>| **show_ui),
+                error_toasts,
+                export_ncube_data,
+                picked_element_tooltip
+                    .after(PickingSystems::Hover)
+                    .before(PickingSystems::Select),
+            ),
+        );
+        #[cfg(not(target_family = "wasm"))]
+        app.add_systems(Update, reload_data_file);
+        #[cfg(target_family = "wasm")]
+        app.add_systems(Startup, load_share_state);
     }
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-struct CameraTransform {
-    translation: Vec3,
-    rotation: Quat,
-    scale: Vec3,
+/// Keeps what is under the windows from being picked, and describes the element hovered
+fn picked_element_tooltip(
+    mut contexts: EguiContexts,
+    mut hovered_element: ResMut<HoveredElement>,
+    q_hypercubes: Query<&crate::hypercube::Hypercube>,
+) {
+    let context = contexts.ctx_mut();
+    if context.is_pointer_over_area() || context.wants_pointer_input() {
+        if hovered_element.is_some() {
+            **hovered_element = None;
+        }
+        return;
+    }
+    let Some(picked) = &**hovered_element else {
+        return;
+    };
+    if let Ok(hypercube) = q_hypercubes.get(picked.hypercube) {
+        egui::show_tooltip_at_pointer(context, egui::Id::new("picked-element"), |ui| {
+            ui.label(picked.element.describe(&hypercube.ncube));
+        });
+    }
 }
-impl_default!(CameraTransform => {
-    let transform = get_default_camera_transform();
-    Self {
-        translation: transform.translation,
-        rotation: transform.rotation,
-        scale: transform.scale,
+
+/// Reloads the most recent data file when R is pressed, whether or not the controls are shown
+#[cfg(not(target_family = "wasm"))]
+fn reload_data_file(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut contexts: EguiContexts,
+    mut recent_files: ResMut<RecentFiles>,
+    mut pending_ncube_data: ResMut<PendingNCubeData>,
+    mut error_toasts: ResMut<ErrorToasts>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyR) || contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    if let Some(path) = recent_files.first().cloned() {
+        load_data_file(
+            &path,
+            &mut recent_files,
+            &mut pending_ncube_data,
+            &mut error_toasts,
+        );
     }
-});
+}
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-struct NCubeData {
-    dimension: usize,
-    rotations: Vec<(usize, usize, f64, f64)>,
-    #[serde(default)]
-    camera_transform: CameraTransform,
-    #[serde(default)]
-    orthographic_projection: bool,
-    #[serde(default)]
-    edge_thickness: f32,
-    #[serde(default)]
-    edge_color: Color,
-    #[serde(default)]
-    face_color: Color,
-    #[serde(default)]
-    unlit: bool,
-}
-
-fn info_panel(
-    (
-        mut ncube_dimension,
-        mut ncube,
-        mut ncube_rotations,
-        mut ncube_planes_of_rotation,
-        mut ncube_edge_color,
-        mut ncube_face_color,
-        mut ncube_edge_thickness,
-        mut ncube_vertices_3d,
-        mut ncube_unlit,
-        mut ncube_is_paused,
-    ): (
-        ResMut<NCubeDimension>,
-        ResMut<NCube>,
-        ResMut<NCubeRotations>,
-        ResMut<NCubePlanesOfRotation>,
-        ResMut<NCubeEdgeColor>,
-        ResMut<NCubeFaceColor>,
-        ResMut<NCubeEdgeThickness>,
-        ResMut<NCubeVertices3D>,
-        ResMut<NCubeUnlit>,
-        ResMut<NCubeIsPaused>,
-    ),
-    (
-        mut contexts,
-        mut q_camera,
-        mut drag_drop_event,
-        mut show_controls,
-        mut is_hovering_file,
-        mut dialog,
-    ): (
-        EguiContexts,
-        Query<(&mut Transform, &mut Projection), With<Camera>>,
-        EventReader<FileDragAndDrop>,
-        ResMut<ShowControls>,
-        ResMut<IsHoveringFile>,
-        ResMut<FileDialog>,
-    ),
+/// Loads the state encoded in the fragment of a share link
+#[cfg(target_family = "wasm")]
+fn load_share_state(
+    mut pending_ncube_data: ResMut<PendingNCubeData>,
+    mut error_toasts: ResMut<ErrorToasts>,
 ) {
+    if let Some(state) = get_share_state() {
+        match NCubeData::from_share_string(&state) {
+            Ok(data) => **pending_ncube_data = Some(data),
+            Err(e) => error_toasts.push(format!("invalid share link: {e}")),
+        }
+    }
+}
+
+/// Scene of the settings panel, reset all at once
+#[derive(SystemParam)]
+struct SceneSettings<'w> {
+    background: ResMut<'w, SceneBackground>,
+    lights: ResMut<'w, SceneLights>,
+    ambient_light: ResMut<'w, SceneAmbientLight>,
+    show_grid: ResMut<'w, SceneShowGrid>,
+    show_axes: ResMut<'w, SceneShowAxes>,
+}
+
+/// Data files loaded, dropped and exported from the settings panel
+#[derive(SystemParam)]
+struct DataFileSettings<'w, 's> {
+    drag_drop_event: EventReader<'w, 's, FileDragAndDrop>,
+    is_hovering_file: ResMut<'w, IsHoveringFile>,
+    file_dialog: ResMut<'w, FileDialog>,
+    open_file_dialog: ResMut<'w, OpenFileDialog>,
+    recent_files: ResMut<'w, RecentFiles>,
+    error_toasts: ResMut<'w, ErrorToasts>,
+    load_paused: ResMut<'w, LoadPaused>,
+    pending_ncube_data: ResMut<'w, PendingNCubeData>,
+    pending_data_export: ResMut<'w, PendingDataExport>,
+}
+
+/// Hypercubes of the settings panel, and what is shown of the selected one
+#[derive(SystemParam)]
+struct HypercubeSettings<'w, 's> {
+    commands: Commands<'w, 's>,
+    selected_hypercube: ResMut<'w, SelectedHypercube>,
+    hypercube_order: ResMut<'w, HypercubeOrder>,
+    q_hypercubes: Query<'w, 's, HypercubeComponents>,
+    ncube_cell_dimension: ResMut<'w, NCubeCellDimension>,
+    inspected_vertices: ResMut<'w, InspectedVertices>,
+    skeleton: Local<'s, Option<Skeleton>>,
+}
+
+/// Main camera and the views of a split screen
+#[derive(SystemParam)]
+struct ViewSettings<'w, 's> {
+    q_camera: Query<'w, 's, (&'static mut Transform, &'static mut Projection), With<MainCamera>>,
+    split_screen_views: ResMut<'w, SplitScreenViews>,
+    split_screen_projections: ResMut<'w, SplitScreenProjections>,
+    q_view_cameras: ViewCameraQuery<'w, 's>,
+}
+
+/// Everything the settings panel edits
+#[derive(SystemParam)]
+struct Settings<'w, 's> {
+    show_controls: ResMut<'w, ShowControls>,
+    show_gallery: ResMut<'w, ShowGallery>,
+    show_info_text: ResMut<'w, ShowInfoText>,
+    scene: SceneSettings<'w>,
+    data_files: DataFileSettings<'w, 's>,
+    hypercubes: HypercubeSettings<'w, 's>,
+    views: ViewSettings<'w, 's>,
+    drag_rotation: ResMut<'w, DragRotation>,
+    drag_planes: ResMut<'w, DragPlanes>,
+    drag_momentum: ResMut<'w, DragMomentum>,
+}
+
+fn info_panel(mut contexts: EguiContexts, mut settings: Settings) {
     let context = contexts.ctx_mut();
     egui::Window::new("settings")
         .default_pos((0.0, 0.0))
@@ -114,27 +199,7 @@ fn info_panel(
                     .num_columns(2)
                     .spacing([40.0, 4.0])
                     .striped(true)
-                    .show(ui, |ui| {
-                        render_ui(
-                            ui,
-                            context,
-                            &mut show_controls,
-                            &mut ncube_dimension,
-                            &mut ncube,
-                            &mut ncube_rotations,
-                            &mut ncube_planes_of_rotation,
-                            &mut ncube_edge_color,
-                            &mut ncube_face_color,
-                            &mut ncube_edge_thickness,
-                            &mut ncube_vertices_3d,
-                            &mut ncube_unlit,
-                            &mut ncube_is_paused,
-                            &mut q_camera,
-                            &mut drag_drop_event,
-                            &mut is_hovering_file,
-                            &mut dialog,
-                        )
-                    });
+                    .show(ui, |ui| render_ui(ui, context, &mut settings));
             });
         });
 }
@@ -144,7 +209,7 @@ fn controls_panel(mut contexts: EguiContexts, mut show_controls: ResMut<ShowCont
         .open(&mut show_controls)
         .vscroll(false)
         .resizable(true)
-        .show(&contexts.ctx_mut(), |ui| {
+        .show(contexts.ctx_mut(), |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
                 egui::Grid::new("controls-grid")
                     .num_columns(2)
@@ -163,6 +228,18 @@ fn controls_panel(mut contexts: EguiContexts, mut show_controls: ResMut<ShowCont
                         mono(ui, "drag and drop file");
                         ui.end_row();
 
+                        ui.label("reload data file");
+                        mono(ui, "R");
+                        ui.end_row();
+
+                        ui.label("undo");
+                        mono(ui, "control + Z");
+                        ui.end_row();
+
+                        ui.label("redo");
+                        mono(ui, "control + shift + Z");
+                        ui.end_row();
+
                         ui.label("pause");
                         mono(ui, "space");
                         ui.end_row();
@@ -171,10 +248,18 @@ fn controls_panel(mut contexts: EguiContexts, mut show_controls: ResMut<ShowCont
                         mono(ui, "hold right mouse button + move");
                         ui.end_row();
 
+                        ui.label("rotate hypercube in higher planes");
+                        mono(ui, "hold shift or alt + right mouse button + move");
+                        ui.end_row();
+
                         ui.label("zoom");
                         mono(ui, "hold control + scroll");
                         ui.end_row();
 
+                        ui.label("highlight vertex, edge or face");
+                        mono(ui, "left click");
+                        ui.end_row();
+
                         ui.label("toggle fullscreen");
                         mono(ui, "F");
                         ui.end_row();
@@ -183,86 +268,164 @@ fn controls_panel(mut contexts: EguiContexts, mut show_controls: ResMut<ShowCont
         });
 }
 
-fn render_ui(
-    ui: &mut Ui,
-    context: &mut egui::Context,
-    show_controls: &mut ResMut<ShowControls>,
-    ncube_dimension: &mut ResMut<NCubeDimension>,
-    ncube: &mut ResMut<NCube>,
-    ncube_rotations: &mut ResMut<NCubeRotations>,
-    ncube_planes_of_rotation: &mut ResMut<NCubePlanesOfRotation>,
-    ncube_edge_color: &mut ResMut<NCubeEdgeColor>,
-    ncube_face_color: &mut ResMut<NCubeFaceColor>,
-    ncube_edge_thickness: &mut ResMut<NCubeEdgeThickness>,
-    ncube_vertices_3d: &mut ResMut<NCubeVertices3D>,
-    ncube_unlit: &mut ResMut<NCubeUnlit>,
-    ncube_is_paused: &mut ResMut<NCubeIsPaused>,
-    q_camera: &mut Query<(&mut Transform, &mut Projection), With<Camera>>,
-    drag_drop_event: &mut EventReader<FileDragAndDrop>,
-    is_hovering_file: &mut ResMut<IsHoveringFile>,
-    file_dialog: &mut ResMut<FileDialog>,
+/// Lists the bundled presets, each loaded with one click
+fn gallery_panel(
+    mut contexts: EguiContexts,
+    mut show_gallery: ResMut<ShowGallery>,
+    gallery_presets: Res<GalleryPresets>,
+    mut pending_ncube_data: ResMut<PendingNCubeData>,
 ) {
+    egui::Window::new("gallery")
+        .open(&mut show_gallery)
+        .vscroll(false)
+        .resizable(true)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for preset in gallery_presets.iter() {
+                    let Some(metadata) = &preset.metadata else {
+                        continue;
+                    };
+                    ui.horizontal(|ui| {
+                        ui.strong(&metadata.title);
+                        if ui.small_button("load").clicked() {
+                            **pending_ncube_data = Some(preset.clone());
+                        }
+                    });
+                    ui.label(&metadata.description);
+                    let cubes = preset
+                        .hypercubes
+                        .iter()
+                        .map(|hypercube| format!("{}-cube", hypercube.dimension))
+                        .collect::<Vec<_>>();
+                    ui.weak(format!("{} by {}", cubes.join(", "), metadata.author));
+                    ui.separator();
+                }
+            });
+        });
+}
+
+/// Shows each error in the bottom right corner until it is dismissed
+fn error_toasts(mut contexts: EguiContexts, mut error_toasts: ResMut<ErrorToasts>) {
+    let mut dismissed = None;
+    for (i, message) in error_toasts.iter().enumerate() {
+        egui::Window::new("error")
+            .id(egui::Id::new(("error-toast", i)))
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0 - 60.0 * i as f32])
+            .show(contexts.ctx_mut(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(egui::Color32::RED, message);
+                    if ui.small_button("x").clicked() {
+                        dismissed = Some(i);
+                    }
+                });
+            });
+    }
+    if let Some(i) = dismissed {
+        error_toasts.remove(i);
+    }
+}
+
+fn render_ui(ui: &mut Ui, context: &mut egui::Context, settings: &mut Settings) {
+    let Settings {
+        show_controls,
+        show_gallery,
+        show_info_text,
+        scene,
+        data_files,
+        hypercubes:
+            HypercubeSettings {
+                commands,
+                selected_hypercube,
+                hypercube_order,
+                q_hypercubes,
+                ncube_cell_dimension,
+                inspected_vertices,
+                skeleton,
+            },
+        views:
+            ViewSettings {
+                q_camera,
+                split_screen_views,
+                split_screen_projections,
+                q_view_cameras,
+            },
+        drag_rotation,
+        drag_planes,
+        drag_momentum,
+    } = settings;
     let (mut camera_transform, mut camera_projection) = q_camera.get_single_mut().unwrap();
+    let hypercubes: Vec<(Entity, usize)> = hypercube_order
+        .iter()
+        .filter_map(|entity| Some((*entity, **q_hypercubes.get(*entity).ok()?.dimension)))
+        .collect();
+    let mut hypercube =
+        (***selected_hypercube).and_then(|entity| q_hypercubes.get_mut(entity).ok());
     render_controls_and_reset(
         ui,
         show_controls,
-        ncube_dimension,
-        ncube,
-        ncube_rotations,
-        ncube_planes_of_rotation,
-        ncube_edge_color,
-        ncube_face_color,
-        ncube_edge_thickness,
-        ncube_is_paused,
+        show_gallery,
+        hypercube.as_mut(),
+        scene,
         &mut camera_transform,
+        &mut data_files.pending_ncube_data,
     );
     render_export_data_file(
         ui,
         context,
-        file_dialog,
-        ncube_dimension,
-        ncube_rotations,
-        ncube_edge_color,
-        ncube_face_color,
-        ncube_edge_thickness,
-        ncube_unlit,
-        &camera_transform,
-        &camera_projection,
-    );
-    render_drop_data_file(
-        ui,
-        ncube_dimension,
-        ncube,
-        ncube_rotations,
-        ncube_planes_of_rotation,
-        ncube_edge_color,
-        ncube_face_color,
-        ncube_edge_thickness,
-        ncube_vertices_3d,
-        ncube_unlit,
-        ncube_is_paused,
-        drag_drop_event,
-        is_hovering_file,
-        &mut camera_transform,
-        &mut camera_projection,
+        &mut data_files.file_dialog,
+        &mut data_files.pending_data_export,
     );
-    render_dimensions(ui, ncube_dimension);
-    render_ncube_info(
+    render_drop_data_file(ui, context, data_files);
+    render_hypercubes(
         ui,
-        ncube.vertices.0.len(),
-        ncube.edges.0.len(),
-        ncube.faces.0.len() / 2,
+        commands,
+        selected_hypercube,
+        hypercube_order,
+        &hypercubes,
     );
+    let Some(hypercube) = hypercube.as_mut() else {
+        return;
+    };
+    let n = hypercube.hypercube.ncube.dimensions;
+    render_dimensions(ui, &mut hypercube.dimension);
+    render_extrusion(ui, &mut hypercube.extrusion);
+    render_ncube_info(ui, &hypercube.hypercube.ncube, inspected_vertices, skeleton);
     render_camera_projection(
         ui,
         &mut camera_projection,
         camera_transform.translation.length(),
     );
-    render_lighting(ui, ncube_unlit);
-    render_edge_thickness(ui, ncube_edge_thickness);
-    render_edge_color(ui, ncube_edge_color);
-    render_face_color(ui, ncube_face_color);
-    render_planes_of_rotation(ui, ncube_rotations, ncube_planes_of_rotation);
+    render_split_screen(
+        ui,
+        split_screen_views,
+        split_screen_projections,
+        q_view_cameras,
+        camera_transform.translation.length(),
+    );
+    render_lighting(ui, &mut hypercube.style);
+    render_info_text(ui, show_info_text);
+    render_edge_thickness(ui, &mut hypercube.style);
+    render_edge_color(ui, &mut hypercube.style);
+    render_face_color(ui, &mut hypercube.style);
+    render_face_blend_mode(ui, &mut hypercube.style);
+    render_face_planes(ui, **hypercube.dimension, &mut hypercube.style);
+    render_cells(
+        ui,
+        &hypercube.hypercube.ncube,
+        ncube_cell_dimension,
+        &mut hypercube.highlight,
+    );
+    render_unfolding(ui, &mut hypercube.unfolding, n);
+    render_walk(ui, &mut hypercube.walk, n);
+    render_drag_rotation(ui, drag_rotation, drag_planes, drag_momentum, n);
+    render_scene(ui, scene, &mut hypercube.style);
+    render_planes_of_rotation(
+        ui,
+        &mut hypercube.rotations,
+        &hypercube.hypercube.planes_of_rotation,
+    );
 }
 
 macro_rules! render_row {
@@ -281,82 +444,669 @@ fn render_camera_projection(ui: &mut Ui, camera_projection: &mut Projection, d:
             ui.radio_value(&mut is_ortho, false, "perspective");
             ui.radio_value(&mut is_ortho, true, "orthographic");
             if is_ortho != backup {
-                *camera_projection = get_default_camera_projection(is_ortho.then(|| d));
+                *camera_projection = get_default_camera_projection(is_ortho.then_some(d));
             }
         });
     });
 }
 
-fn render_dimensions(ui: &mut Ui, ncube_dimension: &mut ResMut<NCubeDimension>) {
+type ViewCameraQuery<'w, 's> =
+    Query<'w, 's, (&'static ViewCamera, &'static mut Projection), Without<MainCamera>>;
+
+fn render_hypercubes(
+    ui: &mut Ui,
+    commands: &mut Commands,
+    selected_hypercube: &mut ResMut<SelectedHypercube>,
+    hypercube_order: &mut ResMut<HypercubeOrder>,
+    hypercubes: &[(Entity, usize)],
+) {
+    render_row!("hypercube", ui => {
+        ui.horizontal(|ui| {
+            let mut selected = ***selected_hypercube;
+            egui::ComboBox::from_id_source("hypercube")
+                .selected_text(hypercube_label(hypercubes, selected))
+                .show_ui(ui, |ui| {
+                    for (entity, _) in hypercubes {
+                        let label = hypercube_label(hypercubes, Some(*entity));
+                        ui.selectable_value(&mut selected, Some(*entity), label);
+                    }
+                });
+            if selected != ***selected_hypercube {
+                ***selected_hypercube = selected;
+            }
+            if ui.button("add").clicked() {
+                let n = hypercubes
+                    .iter()
+                    .find(|(entity, _)| Some(*entity) == selected)
+                    .map_or(DEFAULT_DIMENSION, |(_, n)| *n);
+                let entity = commands
+                    .spawn((HypercubeData::new(n).bundle(false), ArrangedHypercube))
+                    .id();
+                hypercube_order.push(entity);
+                ***selected_hypercube = Some(entity);
+            }
+            if ui
+                .add_enabled(hypercubes.len() > 1, egui::Button::new("remove"))
+                .clicked()
+            {
+                if let Some(entity) = selected {
+                    commands.entity(entity).despawn_recursive();
+                    hypercube_order.retain(|e| *e != entity);
+                    ***selected_hypercube = None;
+                }
+            }
+        });
+    });
+}
+
+fn hypercube_label(hypercubes: &[(Entity, usize)], selected: Option<Entity>) -> String {
+    hypercubes
+        .iter()
+        .enumerate()
+        .find(|(_, (entity, _))| Some(*entity) == selected)
+        .map(|(i, (_, n))| format!("{}: {n}-cube", i + 1))
+        .unwrap_or_default()
+}
+
+fn render_split_screen(
+    ui: &mut Ui,
+    split_screen_views: &mut ResMut<SplitScreenViews>,
+    split_screen_projections: &mut ResMut<SplitScreenProjections>,
+    q_view_cameras: &mut ViewCameraQuery,
+    d: f32,
+) {
+    render_row!("split screen", ui => {
+        ui.scope(|ui| {
+            let mut views = ***split_screen_views;
+            ui.radio_value(&mut views, 1, "off");
+            ui.radio_value(&mut views, 2, "2 views");
+            ui.radio_value(&mut views, MAX_VIEWS, format!("{MAX_VIEWS} views"));
+            if views != ***split_screen_views {
+                ***split_screen_views = views;
+            }
+        });
+    });
+    let views = ***split_screen_views;
+    for view in (0..views).filter(|_| views > 1) {
+        render_row!(format!("view {} n-D projection", view + 1), ui => {
+            ui.scope(|ui| {
+                let mut projection = split_screen_projections[view];
+                ui.radio_value(&mut projection, HypercubeProjection::Perspective, "perspective");
+                ui.radio_value(&mut projection, HypercubeProjection::Orthographic, "orthographic");
+                ui.radio_value(&mut projection, HypercubeProjection::CrossSection, "cross-section");
+                if projection != split_screen_projections[view] {
+                    split_screen_projections[view] = projection;
+                }
+            });
+        });
+    }
+    let mut view_cameras: Vec<_> = q_view_cameras.iter_mut().collect();
+    view_cameras.sort_by_key(|(view, _)| ***view);
+    for (view, mut projection) in view_cameras {
+        render_row!(format!("view {} projection", **view + 1), ui => {
+            ui.scope(|ui| {
+                let mut is_ortho = matches!(*projection, Projection::Orthographic(_));
+                let backup = is_ortho;
+                ui.radio_value(&mut is_ortho, false, "perspective");
+                ui.radio_value(&mut is_ortho, true, "orthographic");
+                if is_ortho != backup {
+                    *projection = get_default_camera_projection(is_ortho.then_some(d));
+                }
+            });
+        });
+    }
+}
+
+fn render_dimensions(ui: &mut Ui, hypercube_dimension: &mut Mut<HypercubeDimension>) {
     render_row!("dimensions", ui => {
-        let mut d = ***ncube_dimension;
-        ui.add(egui::Slider::new(&mut d, 3..=9));
-        if d != ***ncube_dimension {
-            ***ncube_dimension = d;
+        let mut d = ***hypercube_dimension;
+        ui.add(egui::Slider::new(&mut d, MIN_DIMENSION..=MAX_DIMENSION));
+        if d != ***hypercube_dimension {
+            ***hypercube_dimension = d;
         }
     });
 }
 
-fn render_ncube_info(ui: &mut Ui, vertices: usize, edges: usize, faces: usize) {
-    render_row!("vertices", ui => { ui.label(vertices.to_string()); });
-    render_row!("edges", ui => { ui.label(edges.to_string()); });
-    render_row!("faces", ui => { ui.label(faces.to_string()); });
+/// Skeleton of the hypercube and its diameter, kept until the dimension changes
+struct Skeleton {
+    dimensions: usize,
+    graph: NSkeleton,
+    diameter: Option<usize>,
+}
+
+fn render_ncube_info(
+    ui: &mut Ui,
+    ncube: &ncube_core::NCube,
+    inspected_vertices: &mut ResMut<InspectedVertices>,
+    skeleton: &mut Option<Skeleton>,
+) {
+    let n = ncube.dimensions;
+    let skeleton = match skeleton {
+        Some(skeleton) if skeleton.dimensions == n => skeleton,
+        _ => {
+            let graph = NSkeleton::new(ncube);
+            skeleton.insert(Skeleton {
+                dimensions: n,
+                diameter: graph.diameter(),
+                graph,
+            })
+        }
+    };
+    let f_vector = ncube.f_vector();
+    render_row!("vertices", ui => { ui.label(f_vector[0].to_string()); });
+    render_row!("edges", ui => { ui.label(f_vector[1].to_string()); });
+    render_row!("faces", ui => { ui.label(f_vector[2].to_string()); });
+    render_row!("f-vector", ui => {
+        let counts = f_vector.iter().map(usize::to_string).collect::<Vec<_>>();
+        ui.label(format!("({})", counts.join(", ")));
+    });
+    render_row!("euler characteristic", ui => {
+        let chi = ncube.euler_characteristic();
+        let expected = 1 + (-1_i64).pow(n as u32 - 1);
+        let check = if chi == expected { "✔" } else { "✖" };
+        ui.label(format!("{chi} = 1 + (-1)^{} {check}", n - 1));
+    });
+    render_row!("vertex degree", ui => {
+        let degrees = (0..f_vector[0]).map(|i| skeleton.graph.degree(i));
+        let (min, max) = (degrees.clone().min(), degrees.max());
+        ui.label(match (min, max) {
+            (Some(min), Some(max)) if min == max => format!("{min}, regular"),
+            (Some(min), Some(max)) => format!("{min} to {max}"),
+            _ => String::new(),
+        });
+    });
+    render_row!("diameter", ui => {
+        ui.label(skeleton.diameter.map_or("disconnected".into(), |d| d.to_string()));
+    });
+    render_row!("automorphisms", ui => {
+        ui.label(format!("{} = 2^{n} · {n}!", ncube.symmetry_count()));
+    });
+
+    let mut vertices = ***inspected_vertices;
+    if vertices.iter().any(|i| *i >= f_vector[0]) {
+        vertices = [0, 0];
+    }
+    for (k, vertex) in vertices.iter_mut().enumerate() {
+        render_row!(format!("vertex {}", ["a", "b"][k]), ui => {
+            egui::ComboBox::from_id_source(("inspected vertex", k))
+                .selected_text(walk::bit_string(*vertex, n))
+                .show_ui(ui, |ui| {
+                    for i in 0..f_vector[0] {
+                        ui.selectable_value(vertex, i, walk::bit_string(i, n));
+                    }
+                });
+        });
+    }
+    let [a, b] = vertices;
+    render_row!("hamming distance", ui => {
+        ui.label((a ^ b).count_ones().to_string());
+    });
+    render_row!("edge distance", ui => {
+        let distance = skeleton.graph.distances(a)[b];
+        ui.label(distance.map_or("no path".into(), |d| d.to_string()));
+    });
+    render_row!("antipodal vertex", ui => {
+        ui.label(walk::bit_string(skeleton.graph.antipode(a), n));
+    });
+    if vertices != ***inspected_vertices {
+        ***inspected_vertices = vertices;
+    }
 }
 
-fn render_lighting(ui: &mut Ui, ncube_unlit: &mut ResMut<NCubeUnlit>) {
+fn render_lighting(ui: &mut Ui, hypercube_style: &mut Mut<HypercubeStyle>) {
     render_row!("realistic lighting", ui => {
-        let mut lit = !***ncube_unlit;
+        let mut lit = !hypercube_style.unlit;
         ui.add(egui::Checkbox::new(&mut lit, ""));
-        ***ncube_unlit = !lit;
+        if lit == hypercube_style.unlit {
+            hypercube_style.unlit = !lit;
+        }
+    });
+}
+
+fn render_info_text(ui: &mut Ui, show_info_text: &mut ResMut<ShowInfoText>) {
+    render_row!("info text", ui => {
+        ui.add(egui::Checkbox::new(show_info_text, ""));
     });
 }
 
-fn render_edge_thickness(ui: &mut Ui, ncube_edge_thickness: &mut ResMut<NCubeEdgeThickness>) {
+fn render_edge_thickness(ui: &mut Ui, hypercube_style: &mut Mut<HypercubeStyle>) {
     render_row!("edge thickness", ui => {
-        ui.add(egui::Slider::new(&mut ***ncube_edge_thickness, 0.0..=0.025));
+        let mut thickness = hypercube_style.edge_thickness;
+        ui.add(egui::Slider::new(&mut thickness, 0.0..=0.025));
+        if thickness != hypercube_style.edge_thickness {
+            hypercube_style.edge_thickness = thickness;
+        }
     });
 }
 
-fn render_edge_color(ui: &mut Ui, ncube_edge_color: &mut ResMut<NCubeEdgeColor>) {
+fn render_edge_color(ui: &mut Ui, hypercube_style: &mut Mut<HypercubeStyle>) {
     render_row!("edge color", ui => {
-        let mut color: [f32; 4] = [
-            ncube_edge_color.r(),
-            ncube_edge_color.g(),
-            ncube_edge_color.b(),
-            ncube_edge_color.a(),
-        ];
-        ui.color_edit_button_rgba_unmultiplied(&mut color);
-        ***ncube_edge_color = Color::rgba_from_array(color);
+        let color = color_button(ui, hypercube_style.edge_color);
+        if color != hypercube_style.edge_color {
+            hypercube_style.edge_color = color;
+        }
     });
 }
 
-fn render_face_color(ui: &mut Ui, ncube_face_color: &mut ResMut<NCubeFaceColor>) {
+fn render_face_color(ui: &mut Ui, hypercube_style: &mut Mut<HypercubeStyle>) {
     render_row!("face color", ui => {
-        let mut color: [f32; 4] = [
-            ncube_face_color.r(),
-            ncube_face_color.g(),
-            ncube_face_color.b(),
-            ncube_face_color.a(),
-        ];
-        ui.color_edit_button_rgba_unmultiplied(&mut color);
-        ***ncube_face_color = Color::rgba_from_array(color);
+        let color = color_button(ui, hypercube_style.face_color);
+        if color != hypercube_style.face_color {
+            hypercube_style.face_color = color;
+        }
+    });
+}
+
+fn render_face_blend_mode(ui: &mut Ui, hypercube_style: &mut Mut<HypercubeStyle>) {
+    render_row!("face blending", ui => {
+        ui.scope(|ui| {
+            let mut mode = hypercube_style.face_blend_mode;
+            ui.radio_value(&mut mode, FaceBlendMode::Additive, "additive");
+            ui.radio_value(&mut mode, FaceBlendMode::Sorted, "sorted");
+            ui.radio_value(&mut mode, FaceBlendMode::WeightedBlended, "weighted blended");
+            if mode != hypercube_style.face_blend_mode {
+                hypercube_style.face_blend_mode = mode;
+            }
+        });
+    });
+}
+
+fn render_face_planes(ui: &mut Ui, dimension: usize, hypercube_style: &mut Mut<HypercubeStyle>) {
+    render_row!("face coloring", ui => {
+        ui.scope(|ui| {
+            let mut by_plane = hypercube_style.face_color_by_plane;
+            ui.radio_value(&mut by_plane, false, "single");
+            ui.radio_value(&mut by_plane, true, "by plane");
+            if by_plane != hypercube_style.face_color_by_plane {
+                hypercube_style.face_color_by_plane = by_plane;
+            }
+        });
+    });
+    render_row!("only selected face planes", ui => {
+        let mut isolate = hypercube_style.isolate_face_planes;
+        ui.add(egui::Checkbox::new(&mut isolate, ""));
+        if isolate != hypercube_style.isolate_face_planes {
+            hypercube_style.isolate_face_planes = isolate;
+        }
+    });
+    if !hypercube_style.face_color_by_plane && !hypercube_style.isolate_face_planes {
+        return;
+    }
+    let alpha = hypercube_style.face_color.a();
+    render_row!("face planes", ui => {
+        ui.horizontal_wrapped(|ui| {
+            for plane in usize::pair_permutations(0, dimension - 1) {
+                let [r, g, b, _] = face_plane_color(plane, dimension, alpha).as_rgba_u8();
+                let is_selected = hypercube_style.selected_face_planes.contains(&plane);
+                let text = egui::RichText::new(format!("q{}q{}", plane.0 + 1, plane.1 + 1))
+                    .monospace()
+                    .color(egui::Color32::from_rgb(r, g, b));
+                if ui.selectable_label(is_selected, text).clicked() {
+                    if is_selected {
+                        hypercube_style.selected_face_planes.remove(&plane);
+                    } else {
+                        hypercube_style.selected_face_planes.insert(plane);
+                    }
+                }
+            }
+        });
+    });
+}
+
+fn render_cells(
+    ui: &mut Ui,
+    ncube: &ncube_core::NCube,
+    ncube_cell_dimension: &mut ResMut<NCubeCellDimension>,
+    hypercube_highlight: &mut Mut<HypercubeHighlight>,
+) {
+    let max_m = ncube.dimensions - 1;
+    render_row!("cell dimensions", ui => {
+        let mut m = (***ncube_cell_dimension).min(max_m);
+        ui.add(egui::Slider::new(&mut m, 1..=max_m));
+        if m != ***ncube_cell_dimension {
+            ***ncube_cell_dimension = m;
+        }
+    });
+    render_row!("highlighted cell", ui => {
+        let m = (***ncube_cell_dimension).min(max_m);
+        let mut cell = hypercube_highlight.cell.clone();
+        let text = |cell: &Option<InnerNCell>| match cell {
+            Some(cell) => format!("{cell}"),
+            None => String::from("none"),
+        };
+        egui::ComboBox::from_id_source("highlighted-cell")
+            .selected_text(text(&cell))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut cell, None, text(&None));
+                for c in ncube.cells(m).into_iter().map(Some) {
+                    let label = text(&c);
+                    ui.selectable_value(&mut cell, c, label);
+                }
+            });
+        if cell != hypercube_highlight.cell {
+            hypercube_highlight.cell = cell;
+        }
+    });
+    render_row!("fade other cells", ui => {
+        let mut fade = hypercube_highlight.fade_unhighlighted;
+        ui.add(egui::Checkbox::new(&mut fade, ""));
+        if fade != hypercube_highlight.fade_unhighlighted {
+            hypercube_highlight.fade_unhighlighted = fade;
+        }
+    });
+    render_row!("highlight color", ui => {
+        let color = color_button(ui, hypercube_highlight.color);
+        if color != hypercube_highlight.color {
+            hypercube_highlight.color = color;
+        }
+    });
+}
+
+/// Seconds it takes to unfold the hypercube, or fold it back
+const UNFOLD_DURATION: f64 = 3.0;
+
+/// Next order of `axes` in lexicographic order, back to the first after the last
+fn next_permutation(axes: &mut [usize]) {
+    let Some(i) = (1..axes.len()).rev().find(|i| axes[i - 1] < axes[*i]) else {
+        axes.reverse();
+        return;
+    };
+    let j = (i..axes.len())
+        .rev()
+        .find(|j| axes[i - 1] < axes[*j])
+        .unwrap();
+    axes.swap(i - 1, j);
+    axes[i..].reverse();
+}
+
+fn render_walk(ui: &mut Ui, hypercube_walk: &mut Mut<HypercubeWalk>, n: usize) {
+    let mut walk = (**hypercube_walk).clone();
+    render_row!("walk", ui => {
+        ui.horizontal(|ui| {
+            ui.add(egui::Checkbox::new(&mut walk.enabled, ""));
+            let is_gray_code = walk.path == WalkPath::GrayCode;
+            if ui.radio(is_gray_code, "gray code").clicked() && !is_gray_code {
+                walk.path = WalkPath::GrayCode;
+                walk.position = 0.0;
+            }
+            if ui.radio(!is_gray_code, "shortest path").clicked() && is_gray_code {
+                // Across the hypercube, to the antipodal vertex
+                let from = walk.vertex(n);
+                walk.path = WalkPath::Shortest {
+                    from,
+                    axes: walk::differing_axes(from, from ^ ((1 << n) - 1)),
+                };
+                walk.position = 0.0;
+            }
+        });
+    });
+    if let WalkPath::Shortest { from, axes } = &walk.path {
+        let to = walk::shortest_path(*from, axes)
+            .last()
+            .copied()
+            .unwrap_or(*from);
+        let (mut new_from, mut new_to) = (*from, to);
+        for (label, vertex) in [("walk from", &mut new_from), ("walk to", &mut new_to)] {
+            render_row!(label, ui => {
+                egui::ComboBox::from_id_source(label)
+                    .selected_text(walk::bit_string(*vertex, n))
+                    .show_ui(ui, |ui| {
+                        for i in 0..1 << n {
+                            ui.selectable_value(vertex, i, walk::bit_string(i, n));
+                        }
+                    });
+            });
+        }
+        let mut axes = axes.clone();
+        if (new_from, new_to) != (*from, to) {
+            axes = walk::differing_axes(new_from, new_to);
+        }
+        render_row!("bit flips", ui => {
+            ui.horizontal(|ui| {
+                let flips = axes.iter().map(|d| format!("q{}", d + 1)).collect::<Vec<_>>();
+                ui.label(flips.join(" → "));
+                if ui.add_enabled(axes.len() > 1, egui::Button::new("next path")).clicked() {
+                    next_permutation(&mut axes);
+                }
+            });
+        });
+        let path = WalkPath::Shortest {
+            from: new_from,
+            axes,
+        };
+        if path != walk.path {
+            walk.path = path;
+            walk.position = 0.0;
+        }
+    }
+    render_row!("walk speed", ui => {
+        ui.add(egui::Slider::new(&mut walk.speed, 0.1..=10.0).suffix(" edges/s"));
+    });
+    if walk != **hypercube_walk {
+        **hypercube_walk = walk;
+    }
+}
+
+fn render_drag_rotation(
+    ui: &mut Ui,
+    drag_rotation: &mut ResMut<DragRotation>,
+    drag_planes: &mut ResMut<DragPlanes>,
+    drag_momentum: &mut ResMut<DragMomentum>,
+    n: usize,
+) {
+    let mut enabled = ***drag_rotation;
+    render_row!("drag to rotate hypercube", ui => {
+        ui.add(egui::Checkbox::new(&mut enabled, ""));
+    });
+    if enabled != ***drag_rotation {
+        ***drag_rotation = enabled;
+    }
+    if !enabled {
+        return;
+    }
+    let plane_label = |plane: (usize, usize)| format!("q{}q{}", plane.0 + 1, plane.1 + 1);
+    let mut planes = ***drag_planes;
+    for (label, plane) in ["horizontal drag plane", "vertical drag plane"]
+        .into_iter()
+        .zip(planes.iter_mut())
+    {
+        render_row!(label, ui => {
+            egui::ComboBox::from_id_source(label)
+                .selected_text(plane_label(*plane))
+                .show_ui(ui, |ui| {
+                    for j in 1..n {
+                        for i in 0..j {
+                            ui.selectable_value(plane, (i, j), plane_label((i, j)));
+                        }
+                    }
+                });
+        });
+    }
+    if planes != ***drag_planes {
+        ***drag_planes = planes;
+    }
+    let mut momentum = ***drag_momentum;
+    render_row!("keep drag momentum", ui => {
+        ui.add(egui::Checkbox::new(&mut momentum, ""));
+    });
+    if momentum != ***drag_momentum {
+        ***drag_momentum = momentum;
+    }
+}
+
+fn render_extrusion(ui: &mut Ui, hypercube_extrusion: &mut Mut<HypercubeExtrusion>) {
+    render_row!("animate dimension changes", ui => {
+        let mut enabled = hypercube_extrusion.enabled;
+        ui.add(egui::Checkbox::new(&mut enabled, ""));
+        if enabled != hypercube_extrusion.enabled {
+            hypercube_extrusion.enabled = enabled;
+        }
+    });
+}
+
+fn render_unfolding(
+    ui: &mut Ui,
+    hypercube_unfolding: &mut Mut<HypercubeUnfolding>,
+    dimension: usize,
+) {
+    let mut unfolding = (**hypercube_unfolding).clone();
+    render_row!("unfold into net", ui => {
+        ui.add(egui::Checkbox::new(&mut unfolding.enabled, ""));
+    });
+    if unfolding.enabled && dimension > 4 {
+        render_row!("unfold down to 3D", ui => {
+            let checkbox = ui.add(egui::Checkbox::new(&mut unfolding.recursive, ""));
+            checkbox.on_hover_text(format!(
+                "each facet unfolded into its own net in turn, rather than the {}D net \
+                 projected into 3D like the hypercube",
+                dimension - 1
+            ));
+        });
+    }
+    if unfolding.enabled {
+        render_row!("fold angle", ui => {
+            ui.horizontal(|ui| {
+                let mut degrees = unfolding.angle.to_degrees();
+                let slider = ui.add(egui::Slider::new(&mut degrees, 0.0..=90.0).suffix("°"));
+                if slider.changed() {
+                    unfolding.angle = degrees.to_radians();
+                    unfolding.velocity = 0.0;
+                }
+                let velocity = std::f64::consts::FRAC_PI_2 / UNFOLD_DURATION;
+                if ui.button("unfold").clicked() {
+                    unfolding.velocity = velocity;
+                }
+                if ui.button("fold").clicked() {
+                    unfolding.velocity = -velocity;
+                }
+            });
+        });
+    }
+    if unfolding != **hypercube_unfolding {
+        **hypercube_unfolding = unfolding;
+    }
+}
+
+fn color_button(ui: &mut Ui, color: Color) -> Color {
+    let mut color = [color.r(), color.g(), color.b(), color.a()];
+    ui.color_edit_button_rgba_unmultiplied(&mut color);
+    Color::rgba_from_array(color)
+}
+
+fn render_scene(ui: &mut Ui, scene: &mut SceneSettings, hypercube_style: &mut Mut<HypercubeStyle>) {
+    let SceneSettings {
+        background: scene_background,
+        lights: scene_lights,
+        ambient_light: scene_ambient_light,
+        show_grid: scene_show_grid,
+        show_axes: scene_show_axes,
+    } = scene;
+    render_row!("scene preset", ui => {
+        egui::ComboBox::from_id_source("scene-preset")
+            .selected_text("select")
+            .show_ui(ui, |ui| {
+                for preset in scene_presets() {
+                    if ui.selectable_label(false, preset.name).clicked() {
+                        ***scene_background = preset.background;
+                        ***scene_lights = preset.lights;
+                        ***scene_ambient_light = preset.ambient_light;
+                        hypercube_style.edge_color = preset.edge_color;
+                        hypercube_style.face_color = preset.face_color;
+                        hypercube_style.face_blend_mode = preset.face_blend_mode;
+                    }
+                }
+            });
+    });
+    render_row!("background", ui => {
+        ui.horizontal(|ui| {
+            let mut background = match ***scene_background {
+                Background::Color(color) => Background::Color(color_button(ui, color)),
+                Background::Gradient(top, bottom) => {
+                    Background::Gradient(color_button(ui, top), color_button(ui, bottom))
+                }
+            };
+            let mut is_gradient = matches!(background, Background::Gradient(..));
+            if ui.checkbox(&mut is_gradient, "gradient").changed() {
+                background = match background {
+                    Background::Color(color) => Background::Gradient(color, color),
+                    Background::Gradient(_, bottom) => Background::Color(bottom),
+                };
+            }
+            if background != ***scene_background {
+                ***scene_background = background;
+            }
+        });
+    });
+    render_row!("ambient light", ui => {
+        let mut brightness = ***scene_ambient_light;
+        ui.add(egui::Slider::new(&mut brightness, 0.0..=1000.0));
+        if brightness != ***scene_ambient_light {
+            ***scene_ambient_light = brightness;
+        }
+    });
+    let mut lights = scene_lights.to_vec();
+    let mut removed = None;
+    for (i, light) in lights.iter_mut().enumerate() {
+        render_row!(format!("light {}", i + 1), ui => {
+            ui.horizontal(|ui| {
+                for coordinate in [&mut light.position.x, &mut light.position.y, &mut light.position.z]
+                {
+                    ui.add(egui::DragValue::new(coordinate).speed(0.1 * SIZE));
+                }
+                light.color = color_button(ui, light.color);
+                ui.add(
+                    egui::Slider::new(&mut light.intensity, 0.0..=1e7 * SIZE.powi(2))
+                        .logarithmic(true)
+                        .show_value(false),
+                );
+                if ui.button("remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+        });
+    }
+    if let Some(i) = removed {
+        lights.remove(i);
+    }
+    render_row!("lights", ui => {
+        if ui.button("add light").clicked() {
+            lights.push(default_scene_lights()[0]);
+        }
+    });
+    if lights != ***scene_lights {
+        ***scene_lights = lights;
+    }
+    render_row!("grid", ui => {
+        let mut show = ***scene_show_grid;
+        ui.add(egui::Checkbox::new(&mut show, ""));
+        if show != ***scene_show_grid {
+            ***scene_show_grid = show;
+        }
+    });
+    render_row!("axes", ui => {
+        let mut show = ***scene_show_axes;
+        ui.add(egui::Checkbox::new(&mut show, ""));
+        if show != ***scene_show_axes {
+            ***scene_show_axes = show;
+        }
     });
 }
 
 fn render_planes_of_rotation(
     ui: &mut Ui,
-    ncube_rotations: &mut ResMut<NCubeRotations>,
-    ncube_planes_of_rotation: &mut ResMut<NCubePlanesOfRotation>,
+    hypercube_rotations: &mut Mut<HypercubeRotations>,
+    planes_of_rotation: &[(usize, usize)],
 ) {
-    for i in 0..ncube_planes_of_rotation.len() {
-        let plane = ncube_planes_of_rotation[i];
-        let (angle, vel) = *ncube_rotations.get(&plane).unwrap();
+    for plane in planes_of_rotation {
+        let Some(&(angle, vel)) = hypercube_rotations.get(plane) else {
+            continue;
+        };
         let mut tmp = vel;
         render_row!(format!("q{}q{} w", plane.0 + 1, plane.1 + 1), ui => {
             ui.add(egui::Slider::new(&mut tmp, -3.0..=3.0));
         });
         if tmp != vel {
-            ncube_rotations.insert(plane, (angle, tmp));
+            hypercube_rotations.insert(*plane, (angle, tmp));
         }
     }
 }
@@ -364,33 +1114,40 @@ fn render_planes_of_rotation(
 fn render_controls_and_reset(
     ui: &mut Ui,
     show_controls: &mut ResMut<ShowControls>,
-    ncube_dimension: &mut ResMut<NCubeDimension>,
-    ncube: &mut ResMut<NCube>,
-    ncube_rotations: &mut ResMut<NCubeRotations>,
-    ncube_planes_of_rotation: &mut ResMut<NCubePlanesOfRotation>,
-    ncube_edge_color: &mut ResMut<NCubeEdgeColor>,
-    ncube_face_color: &mut ResMut<NCubeFaceColor>,
-    ncube_edge_thickness: &mut ResMut<NCubeEdgeThickness>,
-    ncube_is_paused: &mut ResMut<NCubeIsPaused>,
+    show_gallery: &mut ResMut<ShowGallery>,
+    mut hypercube: Option<&mut HypercubeComponentsItem>,
+    scene: &mut SceneSettings,
     camera_transform: &mut Transform,
+    pending_ncube_data: &mut ResMut<PendingNCubeData>,
 ) {
     ui.scope(|ui| {
         ui.spacing_mut().item_spacing = egui::vec2(4.0, 0.0);
         if ui.button("controls").clicked() {
             ***show_controls = true;
         }
+        if ui.button("gallery").clicked() {
+            ***show_gallery = true;
+        }
         if ui.button("reset").clicked() {
-            **ncube_dimension = NCubeDimension::default();
-            **ncube = NCube::default();
-            **ncube_planes_of_rotation = NCubePlanesOfRotation::default();
-            **ncube_rotations = NCubeRotations::default();
+            if let Some(hypercube) = hypercube.as_deref_mut() {
+                HypercubeData::default().apply(hypercube);
+            }
             *camera_transform = crate::camera::get_default_camera_transform();
-            **ncube_edge_thickness = NCubeEdgeThickness::default();
-            **ncube_face_color = NCubeFaceColor::default();
-            **ncube_edge_color = NCubeEdgeColor::default();
+            *scene.background = SceneBackground::default();
+            *scene.lights = SceneLights::default();
+            *scene.ambient_light = SceneAmbientLight::default();
+            *scene.show_grid = SceneShowGrid::default();
+            *scene.show_axes = SceneShowAxes::default();
+        }
+        if ui
+            .button("start fresh")
+            .on_hover_text("discard the last session and every setting")
+            .clicked()
+        {
+            ***pending_ncube_data = Some(NCubeData::default());
         }
     });
-    if ***ncube_is_paused {
+    if hypercube.is_some_and(|hypercube| **hypercube.paused) {
         ui.colored_label(egui::Color32::RED, "paused");
     } else {
         ui.colored_label(egui::Color32::GREEN, "running");
@@ -402,133 +1159,104 @@ fn render_export_data_file(
     ui: &mut Ui,
     _context: &mut egui::Context,
     _file_dialog: &mut ResMut<FileDialog>,
-    ncube_dimension: &ResMut<NCubeDimension>,
-    ncube_rotations: &ResMut<NCubeRotations>,
-    ncube_edge_color: &ResMut<NCubeEdgeColor>,
-    ncube_face_color: &ResMut<NCubeFaceColor>,
-    ncube_edge_thickness: &ResMut<NCubeEdgeThickness>,
-    ncube_unlit: &ResMut<NCubeUnlit>,
-    camera_transform: &Transform,
-    camera_projection: &Projection,
+    pending_data_export: &mut ResMut<PendingDataExport>,
 ) {
-    let ncube_data = NCubeData {
-        dimension: ***ncube_dimension,
-        rotations: ncube_rotations
-            .iter()
-            .map(|(k, v)| (k.0, k.1, v.0, v.1))
-            .collect(),
-        edge_thickness: ***ncube_edge_thickness,
-        edge_color: ***ncube_edge_color,
-        face_color: ***ncube_face_color,
-        camera_transform: CameraTransform {
-            translation: camera_transform.translation,
-            rotation: camera_transform.rotation,
-            scale: camera_transform.scale,
-        },
-        unlit: ***ncube_unlit,
-        orthographic_projection: matches!(camera_projection, Projection::Orthographic(_)),
-    };
-
-    if ui.button("export to data file").clicked() {
-        #[cfg(not(target_family = "wasm"))]
-        {
-            let mut dialog = egui_file::FileDialog::select_folder(home::home_dir())
-                .title(format!("select folder to save data file").as_str());
-            dialog.open();
-            ***_file_dialog = Some(dialog);
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing = egui::vec2(4.0, 0.0);
+        if ui.button("export to data file").clicked() {
+            #[cfg(not(target_family = "wasm"))]
+            {
+                let mut dialog = egui_file::FileDialog::select_folder(home::home_dir())
+                    .title("select folder to save data file");
+                dialog.open();
+                ***_file_dialog = Some(dialog);
+            }
+            #[cfg(target_family = "wasm")]
+            {
+                ***pending_data_export = Some(DataExport::Download);
+            }
         }
         #[cfg(target_family = "wasm")]
-        if let Ok(data) = serde_json::to_string(&ncube_data) {
-            export_to_data_file(***ncube_dimension, data);
+        if ui.button("copy share link").clicked() {
+            ***pending_data_export = Some(DataExport::ShareLink);
         }
-    }
+    });
 
     #[cfg(not(target_family = "wasm"))]
-    {
-        let dialog = match &mut ***_file_dialog {
-            Some(v) => {
-                v.show(_context);
-                if !v.selected() {
-                    return;
-                }
-                v
-            }
-            None => {
-                return;
+    if let Some(dialog) = &mut ***_file_dialog {
+        if dialog.show(_context).selected() {
+            if let Some(path) = dialog.path() {
+                ***pending_data_export = Some(DataExport::File(path.to_path_buf()));
             }
-        };
-
-        let file_name = format!(
-            "{}cube-{}.data",
-            ***ncube_dimension,
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-        );
+            ***_file_dialog = None;
+        }
+    }
+}
 
-        let mut file = match dialog
-            .path()
-            .and_then(|file_path| std::fs::File::create(file_path.join(file_name)).ok())
-        {
-            Some(v) => v,
-            None => {
-                return;
+fn export_ncube_data(
+    mut pending_data_export: ResMut<PendingDataExport>,
+    mut error_toasts: ResMut<ErrorToasts>,
+    ncube_state: NCubeState,
+) {
+    // A hypercube flattened into a lower dimension keeps the vertices of the higher one until it
+    // is rebuilt, so it is exported once rebuilt
+    if ncube_state.is_rebuilding() {
+        return;
+    }
+    let Some(export) = pending_data_export.take() else {
+        return;
+    };
+    let ncube_data = ncube_state.snapshot();
+    let dimension = ncube_data
+        .hypercubes
+        .get(ncube_data.selected)
+        .map_or(DEFAULT_DIMENSION, |hypercube| hypercube.dimension);
+    match export {
+        #[cfg(not(target_family = "wasm"))]
+        DataExport::File(folder) => {
+            let file_name = format!(
+                "{}cube-{}.data",
+                dimension,
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+            );
+            let result = std::fs::File::create(folder.join(file_name))
+                .map_err(|e| e.to_string())
+                .and_then(|mut file| {
+                    serde_json::to_writer_pretty(&mut file, &ncube_data).map_err(|e| e.to_string())
+                });
+            if let Err(e) = result {
+                error_toasts.push(format!("could not export data file: {e}"));
             }
-        };
-
-        serde_json::to_writer_pretty(&mut file, &ncube_data).unwrap_or_else(|_| {});
+        }
+        #[cfg(target_family = "wasm")]
+        DataExport::Download => {
+            if let Ok(data) = serde_json::to_string(&ncube_data) {
+                export_to_data_file(dimension, data);
+            }
+        }
+        #[cfg(target_family = "wasm")]
+        DataExport::ShareLink => copy_share_link(ncube_data.to_share_string()),
     }
 }
 
 fn render_drop_data_file(
     ui: &mut Ui,
-    ncube_dimension: &mut ResMut<NCubeDimension>,
-    ncube: &mut ResMut<NCube>,
-    ncube_rotations: &mut ResMut<NCubeRotations>,
-    ncube_planes_of_rotation: &mut ResMut<NCubePlanesOfRotation>,
-    ncube_edge_color: &mut ResMut<NCubeEdgeColor>,
-    ncube_face_color: &mut ResMut<NCubeFaceColor>,
-    ncube_edge_thickness: &mut ResMut<NCubeEdgeThickness>,
-    ncube_vertices_3d: &mut ResMut<NCubeVertices3D>,
-    ncube_unlit: &mut ResMut<NCubeUnlit>,
-    ncube_is_paused: &mut ResMut<NCubeIsPaused>,
-    drag_drop_event: &mut EventReader<FileDragAndDrop>,
-    is_hovering_file: &mut ResMut<IsHoveringFile>,
-    camera_transform: &mut Transform,
-    camera_projection: &mut Projection,
+    _context: &mut egui::Context,
+    data_files: &mut DataFileSettings,
 ) {
-    let mut handle_ncube_data = |data: NCubeData| {
-        *camera_transform = Transform {
-            translation: data.camera_transform.translation,
-            scale: data.camera_transform.scale,
-            rotation: data.camera_transform.rotation,
-        };
-        *camera_projection = get_default_camera_projection(
-            data.orthographic_projection
-                .then(|| camera_transform.translation.length()),
-        );
-        ***ncube_is_paused = true;
-        ***ncube_edge_thickness = data.edge_thickness;
-        ***ncube_edge_color = data.edge_color;
-        ***ncube_face_color = data.face_color;
-        ***ncube_unlit = data.unlit;
-        ***ncube_dimension = data.dimension;
-        ***ncube = InnerNCube::new(***ncube_dimension, SIZE.into());
-        ***ncube_rotations = std::collections::HashMap::new();
-        ***ncube_planes_of_rotation = Vec::new();
-        let mut angles = Vec::new();
-        for (d1, d2, angle, vel) in data.rotations {
-            let angle = angle % std::f64::consts::TAU; // To ensure backwards compatibility
-            ncube_rotations.insert((d1, d2), (angle, vel));
-            ncube_planes_of_rotation.push((d1, d2));
-            angles.push(angle);
-        }
-        ***ncube_vertices_3d = ncube
-            .rotate(&ncube_planes_of_rotation, &angles)
-            .perspective_project_vertices();
-    };
-
+    let DataFileSettings {
+        drag_drop_event: _drag_drop_event,
+        is_hovering_file,
+        open_file_dialog: _open_file_dialog,
+        recent_files: _recent_files,
+        error_toasts,
+        load_paused,
+        pending_ncube_data,
+        ..
+    } = data_files;
     ui.colored_label(
         if ***is_hovering_file {
             egui::Color32::GREEN
@@ -537,40 +1265,143 @@ fn render_drop_data_file(
         },
         "drop data file",
     );
+    ui.checkbox(load_paused, "load paused");
     ui.end_row();
 
-    if cfg!(target_family = "wasm") {
-        if let Some(data_str) = get_drag_drop_data() {
-            match serde_json::from_str::<NCubeData>(&data_str) {
-                Ok(data) => handle_ncube_data(data),
-                Err(e) => eprintln!("ERR {e}"),
-            }
+    #[cfg(target_family = "wasm")]
+    if let Some(data_str) = get_drag_drop_data() {
+        match NCubeData::parse(&data_str) {
+            Ok(data) => ***pending_ncube_data = Some(data),
+            Err(e) => error_toasts.push(e.to_string()),
         }
-        return;
     }
 
-    let event = match drag_drop_event.read().nth(0) {
-        Some(v) => v,
-        None => {
-            return;
+    #[cfg(not(target_family = "wasm"))]
+    {
+        let mut path_to_load = None;
+
+        if ui.button("open data file").clicked() {
+            let mut dialog = egui_file::FileDialog::open_file(
+                _recent_files.first().cloned().or_else(home::home_dir),
+            )
+            .show_files_filter(Box::new(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "data")
+            }))
+            .title("open data file");
+            dialog.open();
+            ***_open_file_dialog = Some(dialog);
         }
-    };
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing = egui::vec2(4.0, 0.0);
+            if ui
+                .add_enabled(!_recent_files.is_empty(), egui::Button::new("reload"))
+                .clicked()
+            {
+                path_to_load = _recent_files.first().cloned();
+            }
+            egui::ComboBox::from_id_source("recent-files")
+                .selected_text("recent files")
+                .show_ui(ui, |ui| {
+                    for path in _recent_files.iter() {
+                        if ui
+                            .selectable_label(false, path.display().to_string())
+                            .clicked()
+                        {
+                            path_to_load = Some(path.clone());
+                        }
+                    }
+                });
+        });
+        ui.end_row();
 
-    match event {
-        FileDragAndDrop::HoveredFile { .. } => {
-            ***is_hovering_file = true;
+        if let Some(dialog) = &mut ***_open_file_dialog {
+            if dialog.show(_context).selected() {
+                path_to_load = dialog.path().map(|path| path.to_path_buf());
+                ***_open_file_dialog = None;
+            }
         }
-        FileDragAndDrop::DroppedFile { path_buf, .. } => {
-            let file = std::fs::File::open(&path_buf).unwrap();
-            let reader = std::io::BufReader::new(file);
-            match serde_json::from_reader::<_, NCubeData>(reader) {
-                Ok(data) => handle_ncube_data(data),
-                Err(e) => eprintln!("ERR {e}"),
+
+        match _drag_drop_event.read().next() {
+            Some(FileDragAndDrop::HoveredFile { .. }) => {
+                ***is_hovering_file = true;
             }
-            ***is_hovering_file = false;
+            Some(FileDragAndDrop::DroppedFile { path_buf, .. }) => {
+                path_to_load = Some(path_buf.clone());
+                ***is_hovering_file = false;
+            }
+            Some(_) => {
+                ***is_hovering_file = false;
+            }
+            None => {}
         }
-        _ => {
-            ***is_hovering_file = false;
+
+        if let Some(path) = path_to_load {
+            load_data_file(&path, _recent_files, pending_ncube_data, error_toasts);
+        }
+    }
+}
+
+/// Reads a data file to be applied on the next frame, and moves it to the top of the recent files
+#[cfg(not(target_family = "wasm"))]
+fn load_data_file(
+    path: &std::path::Path,
+    recent_files: &mut RecentFiles,
+    pending_ncube_data: &mut PendingNCubeData,
+    error_toasts: &mut ErrorToasts,
+) {
+    match NCubeData::read(path) {
+        Ok(data) => {
+            **pending_ncube_data = Some(data);
+            crate::config::push_recent_file(recent_files, path);
         }
+        Err(e) => error_toasts.push(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hypercube::tests::app;
+    use crate::hypercube::HypercubeBundle;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+    #[test]
+    fn export_after_flatten() {
+        let mut app = app(ResourcesPlugin);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            250,
+        )))
+        .add_systems(Update, export_ncube_data);
+        app.world.spawn((
+            Transform::from_xyz(0.0, 0.0, 5.0),
+            Projection::default(),
+            MainCamera,
+        ));
+        let mut bundle = HypercubeBundle::new(4);
+        bundle.extrusion.enabled = true;
+        let entity = app.world.spawn(bundle).id();
+        app.update();
+        let folder = std::env::temp_dir().join(format!("ncube-export-{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let exported = |folder: &std::path::Path| {
+            std::fs::read_dir(folder)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect::<Vec<_>>()
+        };
+
+        // Exported only once the tesseract is rebuilt as a cube
+        app.world.get_mut::<HypercubeDimension>(entity).unwrap().0 = 3;
+        app.update();
+        **app.world.resource_mut::<PendingDataExport>() = Some(DataExport::File(folder.clone()));
+        app.update();
+        assert!(exported(&folder).is_empty());
+        (0..8).for_each(|_| app.update());
+        let files = exported(&folder);
+        assert_eq!(files.len(), 1);
+        let data = NCubeData::parse(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+        assert_eq!(data.hypercubes[0].dimension, 3);
+        std::fs::remove_dir_all(&folder).unwrap();
     }
 }
@@ -1,16 +1,25 @@
-use crate::resources::FontHandle;
+use crate::hypercube::{Hypercube, HypercubeDimension, HypercubeRotations, HypercubeWalk};
+use crate::resources::{FontHandle, ResourcesPlugin, SelectedHypercube, ShowInfoText};
 use crate::version_info;
-use crate::NCubeDimension;
-use crate::NCubePlanesOfRotation;
-use crate::NCubeRotations;
 use bevy::prelude::*;
+use ncube_core::walk;
 
 pub struct TextPlugin;
 impl Plugin for TextPlugin {
     fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ResourcesPlugin>() {
+            app.add_plugins(ResourcesPlugin);
+        }
         app.add_systems(Startup, spawn_title_text).add_systems(
             Update,
-            (spawn_info_text, update_title_text, update_info_text).chain(),
+            (
+                spawn_info_text,
+                update_title_text,
+                update_info_text,
+                update_walk_text,
+                update_info_text_visibility,
+            )
+                .chain(),
         );
     }
 }
@@ -19,6 +28,9 @@ impl Plugin for TextPlugin {
 struct TitleText;
 #[derive(Component)]
 struct InfoText;
+/// Bits of the vertex the walk marker last went through, below the angles
+#[derive(Component)]
+struct WalkText;
 
 fn spawn_title_text(mut commands: Commands, mut fonts: ResMut<Assets<Font>>) {
     let font = Vec::from(include_bytes!("../assets/gohufont-14.ttf") as &[u8]);
@@ -71,22 +83,36 @@ fn spawn_title_text(mut commands: Commands, mut fonts: ResMut<Assets<Font>>) {
 }
 
 fn update_title_text(
-    ncube_dimension: Res<NCubeDimension>,
+    selected_hypercube: Res<SelectedHypercube>,
+    q_hypercubes: Query<&HypercubeDimension>,
     mut q_title_text: Query<&mut Text, With<TitleText>>,
 ) {
-    if ncube_dimension.is_changed() {
-        let mut title_text = q_title_text.get_single_mut().unwrap();
-        title_text.sections[0].value = format!("{}-cube", **ncube_dimension);
+    let Some(Ok(dimension)) = selected_hypercube.map(|entity| q_hypercubes.get(entity)) else {
+        return;
+    };
+    let mut title_text = q_title_text.get_single_mut().unwrap();
+    let value = format!("{}-cube", **dimension);
+    if title_text.sections[0].value != value {
+        title_text.sections[0].value = value;
     }
 }
 
+/// Spawns a line of text for each plane of rotation of the selected hypercube, whenever they
+/// change
+#[allow(clippy::type_complexity)]
 fn spawn_info_text(
     mut commands: Commands,
-    ncube_planes_of_rotation: Res<NCubePlanesOfRotation>,
-    q_info_text_entities: Query<Entity, With<InfoText>>,
+    selected_hypercube: Res<SelectedHypercube>,
+    q_hypercubes: Query<&Hypercube>,
+    q_info_text_entities: Query<Entity, Or<(With<InfoText>, With<WalkText>)>>,
     font_handle: Res<FontHandle>,
+    show_info_text: Res<ShowInfoText>,
+    mut shown_planes: Local<Option<Vec<(usize, usize)>>>,
 ) {
-    if !ncube_planes_of_rotation.is_changed() {
+    let planes_of_rotation = selected_hypercube
+        .and_then(|entity| q_hypercubes.get(entity).ok())
+        .map_or(Vec::new(), |hypercube| hypercube.planes_of_rotation.clone());
+    if shown_planes.as_ref() == Some(&planes_of_rotation) {
         return;
     }
 
@@ -99,7 +125,7 @@ fn spawn_info_text(
         font_size: 20.0,
         color: Color::WHITE,
     };
-    for (i, plane) in ncube_planes_of_rotation.iter().enumerate() {
+    for (i, plane) in planes_of_rotation.iter().enumerate() {
         let plane_info = format!("q{}q{}: {:6.1}deg", plane.0 + 1, plane.1 + 1, 0.0);
         commands.spawn((
             TextBundle {
@@ -111,24 +137,43 @@ fn spawn_info_text(
                     right: Val::Px(20.0),
                     ..default()
                 },
+                visibility: info_text_visibility(&show_info_text),
                 ..default()
             },
             InfoText,
         ));
     }
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section("", text_style).with_justify(JustifyText::Right),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(20.0 + 20.0 * (planes_of_rotation.len() as f32 + 1.0)),
+                right: Val::Px(20.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        WalkText,
+    ));
+    *shown_planes = Some(planes_of_rotation);
 }
 
 fn update_info_text(
-    ncube_planes_of_rotation: Res<NCubePlanesOfRotation>,
-    ncube_rotations: Res<NCubeRotations>,
+    selected_hypercube: Res<SelectedHypercube>,
+    q_hypercubes: Query<(&Hypercube, &HypercubeRotations)>,
     mut q_info_text: Query<&mut Text, With<InfoText>>,
 ) {
+    let Some(Ok((hypercube, rotations))) = selected_hypercube.map(|e| q_hypercubes.get(e)) else {
+        return;
+    };
     q_info_text
         .iter_mut()
         .enumerate()
         .for_each(|(i, mut info_text)| {
-            if let Some(plane) = ncube_planes_of_rotation.get(i) {
-                if let Some(value) = ncube_rotations.get(&plane) {
+            if let Some(plane) = hypercube.planes_of_rotation.get(i) {
+                if let Some(value) = rotations.get(plane) {
                     let new_value = format!(
                         "q{}q{}: {:6.1}deg",
                         plane.0 + 1,
@@ -146,3 +191,49 @@ fn update_info_text(
             }
         });
 }
+
+fn update_walk_text(
+    selected_hypercube: Res<SelectedHypercube>,
+    q_hypercubes: Query<(&Hypercube, &HypercubeWalk)>,
+    show_info_text: Res<ShowInfoText>,
+    mut q_walk_text: Query<(&mut Text, &mut Visibility), With<WalkText>>,
+) {
+    let Some(Ok((hypercube, hypercube_walk))) = selected_hypercube.map(|e| q_hypercubes.get(e))
+    else {
+        return;
+    };
+    for (mut walk_text, mut visibility) in &mut q_walk_text {
+        let n = hypercube.ncube.dimensions;
+        let value = format!("walk: {}", walk::bit_string(hypercube_walk.vertex(n), n));
+        if walk_text.sections[0].value != value {
+            walk_text.sections[0].value = value;
+        }
+        let walk_visibility = if hypercube_walk.enabled {
+            info_text_visibility(&show_info_text)
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != walk_visibility {
+            *visibility = walk_visibility;
+        }
+    }
+}
+
+fn info_text_visibility(show_info_text: &ShowInfoText) -> Visibility {
+    if **show_info_text {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn update_info_text_visibility(
+    show_info_text: Res<ShowInfoText>,
+    mut q_info_text: Query<&mut Visibility, With<InfoText>>,
+) {
+    if show_info_text.is_changed() {
+        for mut visibility in &mut q_info_text {
+            *visibility = info_text_visibility(&show_info_text);
+        }
+    }
+}
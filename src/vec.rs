@@ -1,4 +1,6 @@
+use bevy::math::DVec3;
 use bevy::prelude::Vec3;
+use ncube_core::NCube;
 
 #[derive(Debug)]
 pub struct SphericalCoordinate {
@@ -39,80 +41,36 @@ impl SphericalCoordinateSystem for Vec3 {
     }
 }
 
-pub trait TriangleNormal {
-    fn normal(&self, b: &Self, c: &Self) -> Self;
+pub trait PolygonNormal
+where
+    Self: Sized,
+{
+    fn polygon_normal(vertices: &[Self]) -> Self;
 }
 
-impl TriangleNormal for Vec3 {
-    /// Computes the normal for a triangle based on its vertices
-    fn normal(&self, b: &Self, c: &Self) -> Self {
-        let a = self;
-        (*b - *a).cross(*c - *b).normalize()
+impl PolygonNormal for Vec3 {
+    /// Computes the normal for a planar polygon based on its vertices, in cyclic order.
+    /// Uses Newell's method so that every triangle of the polygon shares the same normal.
+    fn polygon_normal(vertices: &[Self]) -> Self {
+        let len = vertices.len();
+        (0..len)
+            .map(|k| vertices[k].cross(vertices[(k + 1) % len]))
+            .sum::<Vec3>()
+            .normalize()
     }
 }
 
-pub trait MathOps {
-    // Computes the dot product between 2 vectors
-    fn dot(&self, b: &Self) -> f64;
-    // Computes the hadamard product between 2 vectors
-    fn hadamard(&self, b: &Self) -> Self;
-    /// Checks how many dimensions are shared across a group of `points` (equal in value)
-    /// Useful for checking whether the points lie within an n dimensional slice.
-    fn shared_dimensions(points: &[&Self]) -> Vec<usize>;
-    /// Computes the distance between 2 vectors
-    fn distance(&self, b: &Self) -> f64;
-    /// Computes the magnitude of a vector
-    fn magnitude(&self) -> f64;
-    /// Normalizes vector
-    fn normalize(&self) -> Self;
-    /// Scales vector
-    fn scale(&self, a: f64) -> Self;
+pub trait Vertices3D {
+    fn vertices_3d(&self) -> Vec<Vec3>;
 }
 
-impl MathOps for Vec<f64> {
-    fn dot(&self, b: &Self) -> f64 {
-        let len = self.len();
-        assert_eq!(len, b.len());
-        (0..len).map(|d| self[d] * b[d]).sum()
-    }
-
-    fn hadamard(&self, b: &Self) -> Self {
-        let len = self.len();
-        assert_eq!(len, b.len());
-        (0..len).map(|d| self[d] * b[d]).collect()
-    }
-
-    fn shared_dimensions(points: &[&Self]) -> Vec<usize> {
-        let mut common_d = Vec::new();
-        for d in 0..points[0].len() {
-            if points.iter().all(|p| p[d] == points[0][d]) {
-                common_d.push(d);
-            }
-        }
-        common_d
-    }
-
-    fn distance(&self, b: &Self) -> f64 {
-        let d = self.len();
-        assert_eq!(d, b.len());
-        self.iter()
-            .enumerate()
-            .map(|(i, x)| (b[i] - x).powi(2))
-            .sum::<f64>()
-            .sqrt()
-    }
-
-    fn magnitude(&self) -> f64 {
-        self.iter().map(|x| x.powi(2)).sum::<f64>().sqrt()
-    }
-
-    fn normalize(&self) -> Self {
-        let l = self.magnitude();
-        self.iter().map(|x| x / l).collect()
-    }
-
-    fn scale(&self, a: f64) -> Self {
-        self.iter().map(|x| a * x).collect()
+impl Vertices3D for NCube {
+    /// Projects the vertices into the 3D scene
+    fn vertices_3d(&self) -> Vec<Vec3> {
+        self.perspective_project_vertices()
+            .into_iter()
+            .map(|vertex| DVec3::from_array(vertex).as_vec3())
+            .collect()
     }
 }
 
@@ -128,4 +86,15 @@ mod tests {
         println!("Comparing {a:?} with {a_prime:?} +- {precision}");
         assert!(a.cmpgt(a_prime - precision).all() && a.cmplt(a_prime + precision).all());
     }
+    #[test]
+    fn polygon_normal() {
+        let square = [
+            Vec3::new(1.0, 1.0, 2.0),
+            Vec3::new(-1.0, 1.0, 2.0),
+            Vec3::new(-1.0, -1.0, 2.0),
+            Vec3::new(1.0, -1.0, 2.0),
+        ];
+        assert_eq!(Vec3::polygon_normal(&square), Vec3::Z);
+        assert_eq!(Vec3::polygon_normal(&square[..3]), Vec3::Z);
+    }
 }
@@ -0,0 +1,516 @@
+//! Arrangement of the hypercubes: side by side in a single view, or each in its own view of a
+//! split screen, seen through cameras that follow the main one. Only those spawned with an
+//! [`ArrangedHypercube`] are moved, the others keep their own transform.
+//!
+//! Each view of a split screen brings its hypercube down into 3D in its own way, see
+//! [`SplitScreenProjections`]. Views past the number of hypercubes show them again through a
+//! [`ViewHypercube`], to compare projections of the same hypercube.
+
+use crate::camera::{get_default_camera_projection, MainCamera};
+use crate::hypercube::{
+    Hypercube, HypercubeBundle, HypercubeComponents, HypercubeDimension, HypercubeExtrusion,
+    HypercubeHighlight, HypercubePaused, HypercubeProjection, HypercubeRotations, HypercubeStyle,
+    HypercubeUnfolding, HypercubeWalk, NCubeSystems,
+};
+use crate::resources::{
+    HypercubeOrder, SelectedHypercube, SplitScreenProjections, SplitScreenViews, SIZE,
+};
+use bevy::prelude::*;
+use bevy::render::camera::{CameraUpdateSystem, Viewport};
+use bevy::render::view::RenderLayers;
+use bevy::transform::TransformSystem;
+use bevy::window::PrimaryWindow;
+use std::collections::HashMap;
+
+pub struct ViewsPlugin;
+
+impl Plugin for ViewsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                (
+                    arrange_hypercubes,
+                    sync_hypercube_rotations,
+                    update_view_hypercubes,
+                )
+                    .chain()
+                    .before(NCubeSystems),
+                update_view_cameras
+                    .before(CameraUpdateSystem)
+                    .before(TransformSystem::TransformPropagate),
+            ),
+        );
+    }
+}
+
+pub const MAX_VIEWS: usize = 4;
+
+/// Layers of every view of a split screen, but not of the weighted blended passes of the views
+pub fn view_layers() -> RenderLayers {
+    RenderLayers::from_layers(&(0..MAX_VIEWS as u8).collect::<Vec<_>>())
+}
+
+/// Distance between the centers of neighboring hypercubes
+const HYPERCUBE_SPACING: f32 = 3.0 * SIZE;
+
+/// Hypercube placed by the views, side by side with the others or centered in its own view,
+/// rather than by its own transform
+#[derive(Component)]
+pub struct ArrangedHypercube;
+
+/// Copy of a hypercube shown again in a view of a split screen with more views than
+/// hypercubes. It follows its `source` rather than turning on its own.
+#[derive(Component)]
+pub struct ViewHypercube {
+    pub source: Entity,
+    pub view: usize,
+}
+
+/// Camera of a split screen view other than the first, which is the main camera's
+#[derive(Component, Deref)]
+pub struct ViewCamera(pub usize);
+
+/// Position of the `i`th of `count` hypercubes, lined up side by side and centered on the origin
+fn hypercube_translation(i: usize, count: usize) -> Vec3 {
+    Vec3::X * (i as f32 - (count as f32 - 1.0) / 2.0) * HYPERCUBE_SPACING
+}
+
+/// Part of the window, of physical `size`, that shows the given view
+fn viewport(view: usize, views: usize, size: UVec2) -> Option<Viewport> {
+    let (columns, rows) = match views {
+        1 => return None,
+        2 => (2, 1),
+        _ => (2, 2),
+    };
+    let view_size = UVec2::new(size.x / columns, size.y / rows);
+    if view_size.min_element() == 0 {
+        return None;
+    }
+    let view = view as u32;
+    Some(Viewport {
+        physical_position: UVec2::new(view % columns, view / columns) * view_size,
+        physical_size: view_size,
+        ..default()
+    })
+}
+
+/// Lines the arranged hypercubes up in a single view. In a split screen, each hypercube is
+/// shown in its own view through the projection of the view, the arranged ones centered in it,
+/// and those without a view are hidden. This is only redone when hypercubes are added or
+/// removed, or the views change.
+#[allow(clippy::type_complexity)]
+fn arrange_hypercubes(
+    mut commands: Commands,
+    split_screen_views: Res<SplitScreenViews>,
+    split_screen_projections: Res<SplitScreenProjections>,
+    hypercube_order: Res<HypercubeOrder>,
+    mut q_hypercubes: Query<
+        (
+            &mut Transform,
+            Option<&RenderLayers>,
+            Option<&HypercubeProjection>,
+            Has<ArrangedHypercube>,
+        ),
+        With<Hypercube>,
+    >,
+) {
+    if !split_screen_views.is_changed()
+        && !split_screen_projections.is_changed()
+        && !hypercube_order.is_changed()
+    {
+        return;
+    }
+    let views = **split_screen_views;
+    let count = hypercube_order
+        .iter()
+        .filter(|entity| {
+            q_hypercubes
+                .get(**entity)
+                .is_ok_and(|(.., is_arranged)| is_arranged)
+        })
+        .count();
+    let mut k = 0;
+    for (i, entity) in hypercube_order.iter().enumerate() {
+        let Ok((mut transform, render_layers, projection, is_arranged)) =
+            q_hypercubes.get_mut(*entity)
+        else {
+            continue;
+        };
+        let (translation, layers) = match views {
+            1 => (hypercube_translation(k, count), RenderLayers::default()),
+            _ if i < views => (Vec3::ZERO, RenderLayers::layer(i as u8)),
+            _ => (Vec3::ZERO, RenderLayers::none()),
+        };
+        let new_projection = match views {
+            1 => HypercubeProjection::default(),
+            _ => split_screen_projections[i.min(MAX_VIEWS - 1)],
+        };
+        if projection.copied().unwrap_or_default() != new_projection {
+            commands.entity(*entity).insert(new_projection);
+        }
+        if is_arranged {
+            k += 1;
+            if transform.translation != translation {
+                transform.translation = translation;
+            }
+        }
+        if render_layers.copied().unwrap_or_default() != layers {
+            commands.entity(*entity).insert(layers);
+        }
+    }
+}
+
+/// How a hypercube was oriented, turned and paused before a split screen synced it
+struct SavedRotations {
+    hypercube: Hypercube,
+    rotations: HypercubeRotations,
+    paused: HypercubePaused,
+}
+
+/// Drives the hypercubes of a split screen with one clock: they all start again at rest, then
+/// turn and pause along with the selected hypercube in the planes they have in common with it.
+/// The others get back how they turned before once the split screen is turned off.
+fn sync_hypercube_rotations(
+    split_screen_views: Res<SplitScreenViews>,
+    selected_hypercube: Res<SelectedHypercube>,
+    mut q_hypercubes: Query<
+        (
+            Entity,
+            &mut Hypercube,
+            &mut HypercubeRotations,
+            &mut HypercubePaused,
+        ),
+        Without<ViewHypercube>,
+    >,
+    mut synced: Local<Vec<(Entity, usize)>>,
+    mut saved: Local<HashMap<Entity, SavedRotations>>,
+) {
+    if **split_screen_views == 1 {
+        synced.clear();
+        for (entity, saved) in saved.drain() {
+            if Some(entity) == **selected_hypercube {
+                continue;
+            }
+            let Ok((_, mut hypercube, mut rotations, mut paused)) = q_hypercubes.get_mut(entity)
+            else {
+                continue;
+            };
+            // Hypercubes rebuilt for another dimension since have nothing to go back to
+            if hypercube.ncube.dimensions == saved.hypercube.ncube.dimensions {
+                *hypercube = saved.hypercube;
+                *rotations = saved.rotations;
+                *paused = saved.paused;
+            }
+        }
+        return;
+    }
+    let Some(Ok((_, _, rotations, paused))) = selected_hypercube.map(|e| q_hypercubes.get(e))
+    else {
+        return;
+    };
+    let velocities: HashMap<(usize, usize), f64> = rotations
+        .iter()
+        .map(|(plane, (_, vel))| (*plane, *vel))
+        .collect();
+    let paused = **paused;
+
+    let mut hypercubes: Vec<(Entity, usize)> = q_hypercubes
+        .iter()
+        .map(|(entity, hypercube, ..)| (entity, hypercube.ncube.dimensions))
+        .collect();
+    hypercubes.sort();
+    let is_restarting = *synced != hypercubes;
+    *synced = hypercubes;
+
+    for (entity, mut hypercube, mut rotations, mut hypercube_paused) in &mut q_hypercubes {
+        if is_restarting {
+            if Some(entity) != **selected_hypercube {
+                saved.entry(entity).or_insert_with(|| SavedRotations {
+                    hypercube: hypercube.clone(),
+                    rotations: rotations.clone(),
+                    paused: *hypercube_paused,
+                });
+            }
+            let planes_of_rotation = hypercube.planes_of_rotation.clone();
+            *hypercube = Hypercube {
+                planes_of_rotation,
+                ..Hypercube::new(hypercube.ncube.dimensions, hypercube.ncube.size)
+            };
+            rotations.values_mut().for_each(|(angle, _)| *angle = 0.0);
+        }
+        let is_out_of_step = rotations
+            .iter()
+            .any(|(plane, (_, vel))| velocities.get(plane).copied().unwrap_or_default() != *vel);
+        if is_out_of_step {
+            for (plane, (_, vel)) in rotations.iter_mut() {
+                *vel = velocities.get(plane).copied().unwrap_or_default();
+            }
+        }
+        if **hypercube_paused != paused {
+            **hypercube_paused = paused;
+        }
+    }
+}
+
+/// Shows the hypercubes again in the views of a split screen past the number of hypercubes,
+/// each view in turn showing the next one, and keeps these copies in step with their source
+#[allow(clippy::type_complexity)]
+fn update_view_hypercubes(
+    mut commands: Commands,
+    split_screen_views: Res<SplitScreenViews>,
+    split_screen_projections: Res<SplitScreenProjections>,
+    hypercube_order: Res<HypercubeOrder>,
+    q_sources: Query<
+        (
+            Ref<Hypercube>,
+            &HypercubeDimension,
+            &HypercubeRotations,
+            &HypercubeStyle,
+            &HypercubeHighlight,
+            &HypercubeUnfolding,
+            &HypercubeWalk,
+        ),
+        Without<ViewHypercube>,
+    >,
+    mut q_copies: Query<(
+        Entity,
+        &ViewHypercube,
+        &mut HypercubeProjection,
+        HypercubeComponents,
+    )>,
+) {
+    let (views, count) = (**split_screen_views, hypercube_order.len());
+    let mut missing: Vec<(usize, Entity)> = match (views, count) {
+        (1, _) | (_, 0) => Vec::new(),
+        _ => (count..views)
+            .map(|view| (view, hypercube_order[view % count]))
+            .collect(),
+    };
+    for (entity, copy, mut projection, mut hypercube) in &mut q_copies {
+        let Some(k) = missing
+            .iter()
+            .position(|shown| *shown == (copy.view, copy.source))
+        else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        missing.swap_remove(k);
+        projection.set_if_neq(split_screen_projections[copy.view]);
+        let Ok((source, dimension, rotations, style, highlight, unfolding, walk)) =
+            q_sources.get(copy.source)
+        else {
+            continue;
+        };
+        // A hypercube being flattened into a lower dimension is only followed once rebuilt
+        if source.is_changed() && source.ncube.dimensions == **dimension {
+            *hypercube.hypercube = source.clone();
+        }
+        hypercube.dimension.set_if_neq(*dimension);
+        if *hypercube.rotations != *rotations {
+            *hypercube.rotations = rotations.clone();
+        }
+        if *hypercube.style != *style {
+            *hypercube.style = style.clone();
+        }
+        if *hypercube.highlight != *highlight {
+            *hypercube.highlight = highlight.clone();
+        }
+        let unfolding = HypercubeUnfolding {
+            velocity: 0.0,
+            ..unfolding.clone()
+        };
+        hypercube.unfolding.set_if_neq(unfolding);
+        if *hypercube.walk != *walk {
+            *hypercube.walk = walk.clone();
+        }
+    }
+
+    for (view, source) in missing {
+        let Ok((hypercube, dimension, rotations, style, highlight, unfolding, walk)) =
+            q_sources.get(source)
+        else {
+            continue;
+        };
+        commands.spawn((
+            HypercubeBundle {
+                hypercube: hypercube.clone(),
+                dimension: *dimension,
+                rotations: rotations.clone(),
+                paused: HypercubePaused(true),
+                style: style.clone(),
+                highlight: highlight.clone(),
+                unfolding: HypercubeUnfolding {
+                    velocity: 0.0,
+                    ..unfolding.clone()
+                },
+                extrusion: HypercubeExtrusion::default(),
+                walk: walk.clone(),
+                spatial: SpatialBundle::default(),
+            },
+            ViewHypercube { source, view },
+            split_screen_projections[view],
+            RenderLayers::layer(view as u8),
+        ));
+    }
+}
+
+/// Splits the window between the main camera and the view cameras, which follow it
+fn update_view_cameras(
+    mut commands: Commands,
+    split_screen_views: Res<SplitScreenViews>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_main_camera: Query<(&Transform, &mut Camera), With<MainCamera>>,
+    mut q_view_cameras: Query<
+        (
+            Entity,
+            &ViewCamera,
+            &mut Transform,
+            &mut Camera,
+            &mut Projection,
+        ),
+        Without<MainCamera>,
+    >,
+    mut distance: Local<f32>,
+) {
+    let (Ok((main_transform, mut main_camera)), Ok(window)) =
+        (q_main_camera.get_single_mut(), q_window.get_single())
+    else {
+        return;
+    };
+    let is_zoomed = main_transform.translation.length() != *distance;
+    *distance = main_transform.translation.length();
+    let views = **split_screen_views;
+    let size = UVec2::new(window.physical_width(), window.physical_height());
+    main_camera.viewport = viewport(0, views, size);
+
+    let mut is_spawned = [false; MAX_VIEWS];
+    for (entity, view, mut transform, mut camera, mut projection) in &mut q_view_cameras {
+        if **view >= views {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        is_spawned[**view] = true;
+        *transform = *main_transform;
+        camera.viewport = viewport(**view, views, size);
+        camera.clear_color = main_camera.clear_color.clone();
+        // Orthographic projections are scaled by the distance to the hypercube
+        if is_zoomed && matches!(*projection, Projection::Orthographic(_)) {
+            *projection = get_default_camera_projection(Some(*distance));
+        }
+    }
+    for view in (1..views).filter(|view| !is_spawned[*view]) {
+        commands.spawn((
+            Camera3dBundle {
+                transform: *main_transform,
+                camera: Camera {
+                    order: view as isize,
+                    viewport: viewport(view, views, size),
+                    clear_color: main_camera.clear_color.clone(),
+                    ..default()
+                },
+                projection: get_default_camera_projection(None),
+                ..default()
+            },
+            ViewCamera(view),
+            RenderLayers::layer(view as u8),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hypercube::tests::app;
+    use crate::hypercube::HypercubeBundle;
+    use crate::resources::ResourcesPlugin;
+    #[test]
+    fn split_viewports() {
+        let size = UVec2::new(800, 600);
+        assert!(viewport(0, 1, size).is_none());
+        let right = viewport(1, 2, size).unwrap();
+        assert_eq!(right.physical_position, UVec2::new(400, 0));
+        assert_eq!(right.physical_size, UVec2::new(400, 600));
+        let bottom_left = viewport(2, 4, size).unwrap();
+        assert_eq!(bottom_left.physical_position, UVec2::new(0, 300));
+        assert_eq!(bottom_left.physical_size, UVec2::new(400, 300));
+    }
+    #[test]
+    fn sync_split_screen_rotations() {
+        let mut app = app((ResourcesPlugin, ViewsPlugin));
+        let tesseract = app
+            .world
+            .spawn((
+                HypercubeBundle {
+                    rotations: HypercubeRotations::new(4, &[((0, 1), 1.0), ((0, 3), 0.5)]),
+                    ..HypercubeBundle::new(4)
+                },
+                ArrangedHypercube,
+            ))
+            .id();
+        let cube = app
+            .world
+            .spawn((
+                HypercubeBundle {
+                    rotations: HypercubeRotations::new(3, &[((1, 2), 2.0)]),
+                    ..HypercubeBundle::new(3)
+                },
+                ArrangedHypercube,
+            ))
+            .id();
+        let placed = Vec3::new(0.0, 5.0, 0.0);
+        let unarranged = app
+            .world
+            .spawn(HypercubeBundle {
+                spatial: SpatialBundle::from_transform(Transform::from_translation(placed)),
+                ..HypercubeBundle::new(3)
+            })
+            .id();
+        **app.world.resource_mut::<SelectedHypercube>() = Some(tesseract);
+        app.update();
+        assert_eq!(
+            app.world.get::<Transform>(cube).unwrap().translation,
+            hypercube_translation(1, 2)
+        );
+        assert_eq!(
+            app.world.get::<Transform>(unarranged).unwrap().translation,
+            placed
+        );
+
+        **app.world.resource_mut::<SplitScreenViews>() = 2;
+        app.update();
+        let rotations = app.world.get::<HypercubeRotations>(cube).unwrap();
+        assert_eq!(rotations[&(0, 1)].1, 1.0);
+        assert_eq!(rotations[&(1, 2)].1, 0.0);
+        assert_eq!(
+            app.world.get::<Transform>(cube).unwrap().translation,
+            Vec3::ZERO
+        );
+        assert_eq!(
+            app.world.get::<RenderLayers>(cube),
+            Some(&RenderLayers::layer(1))
+        );
+
+        **app.world.resource_mut::<SplitScreenViews>() = 1;
+        app.update();
+        let rotations = app.world.get::<HypercubeRotations>(cube).unwrap();
+        assert_eq!(rotations[&(0, 1)].1, 0.0);
+        assert_eq!(rotations[&(1, 2)].1, 2.0);
+        let rotations = app.world.get::<HypercubeRotations>(tesseract).unwrap();
+        assert_eq!(rotations[&(0, 1)].1, 1.0);
+
+        // Once every hypercube has a view, the next views show copies of them
+        **app.world.resource_mut::<SplitScreenViews>() = 4;
+        app.world.resource_mut::<SplitScreenProjections>()[3] = HypercubeProjection::CrossSection;
+        app.update();
+        let mut q_copies = app.world.query::<(&ViewHypercube, &HypercubeProjection)>();
+        let copies = q_copies.iter(&app.world).collect::<Vec<_>>();
+        assert_eq!(copies.len(), 1);
+        assert_eq!(copies[0].0.source, tesseract);
+        assert_eq!(*copies[0].1, HypercubeProjection::CrossSection);
+
+        **app.world.resource_mut::<SplitScreenViews>() = 3;
+        app.update();
+        assert_eq!(q_copies.iter(&app.world).count(), 0);
+    }
+}
@@ -5,9 +5,37 @@ declare global {
   interface Window {
     get_drag_drop_data: () => string | null
     export_to_data_file: (dimension: number, data: string) => void
+    get_share_state: () => string | null
+    copy_share_link: (state: string) => void
+    load_session: () => string | null
+    save_session: (data: string) => void
   }
 }
 
+// Read once, before the app rewrites the fragment
+const shareState = window.location.hash.slice(1) || null
+
+const get_share_state = () => shareState
+
+const SESSION_KEY = 'ncube-session'
+
+const load_session = () => window.localStorage.getItem(SESSION_KEY)
+
+const save_session = (data: string) => {
+  try {
+    window.localStorage.setItem(SESSION_KEY, data)
+  } catch {
+    // Storage is full or disabled, the session is not kept
+  }
+}
+
+const copy_share_link = (state: string) => {
+  const url = new URL(window.location.href)
+  url.hash = state
+  window.history.replaceState(null, '', url)
+  navigator.clipboard.writeText(url.toString())
+}
+
 const export_to_data_file = (dimension: number, data: string) => {
   const fileName = `${dimension}cube-${Math.floor(
     new Date().getTime() / 1000,
@@ -61,6 +89,10 @@ const App = () => {
 
   useEffect(() => {
     window.export_to_data_file = export_to_data_file
+    window.get_share_state = get_share_state
+    window.copy_share_link = copy_share_link
+    window.load_session = load_session
+    window.save_session = save_session
     window.get_drag_drop_data = () => {
       const data = dragDropData
       if (data) setDragDropData(null)